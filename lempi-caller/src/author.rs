@@ -1,17 +1,262 @@
-use primitive_types::H256;
+use bip39::{Language, Mnemonic};
 
-use sp_core::{sr25519, Pair};
+use hmac::{Hmac, Mac};
+
+use primitive_types::{H160, H256};
+
+use serde::{Deserialize, Serialize};
+
+use sha2::Sha512;
+
+use sp_core::{
+    blake2_256,
+    crypto::{AccountId32 as SpAccountId32, Ss58Codec},
+    ecdsa, ed25519, keccak_256, Pair,
+};
 
 use substrate_parser::additional_types::AccountId32;
 
+use crate::crypto::{self, Derive, Pair as _, VrfSecret};
+use crate::keystore::{self, EncryptedSecret, Entry, Keystore};
+use crate::message::wrap_bytes;
+use crate::multisig::{self, Multisig};
+use crate::polkadot_js;
+use crate::sr25519::{
+    self,
+    vrf::{VrfSignData, VrfSignature},
+};
+
 #[derive(Debug)]
 pub enum Error {
     DerivationFailed(String),
+    InvalidAddress(String),
+    InvalidMnemonic(String),
+    Keystore(keystore::Error),
+    Multisig(multisig::Error),
+    NotDerivable(String),
+    NotUnlocked(String),
+    PolkadotJs(polkadot_js::Error),
+    PublicMismatch(String),
+    WrongCrypto(String),
+}
+
+/// Signature scheme of key pair
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Crypto {
+    #[default]
+    Sr25519,
+    Ed25519,
+    Ecdsa,
+    /// secp256k1 with keccak hashing and 20-byte accounts, as on EVM-compatible chains
+    Ethereum,
+}
+
+impl std::str::FromStr for Crypto {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "sr25519" => Ok(Crypto::Sr25519),
+            "ed25519" => Ok(Crypto::Ed25519),
+            "ecdsa" => Ok(Crypto::Ecdsa),
+            "ethereum" | "eth" => Ok(Crypto::Ethereum),
+            a => Err(format!("unknown crypto {}", a)),
+        }
+    }
+}
+
+pub enum KeyPair {
+    Sr25519(sr25519::Pair),
+    Ed25519(ed25519::Pair),
+    Ecdsa(ecdsa::Pair),
+    Ethereum(ecdsa::Pair),
+}
+
+/// Signature, as it goes into `MultiSignature` or `EthereumSignature`
+pub enum Signature {
+    Sr25519([u8; 64]),
+    Ed25519([u8; 64]),
+    Ecdsa([u8; 65]),
+    Ethereum([u8; 65]),
+}
+
+impl Signature {
+    /// Name of matching `MultiSignature` variant
+    pub fn variant_name(&self) -> &str {
+        match self {
+            Signature::Sr25519(_) => "Sr25519",
+            Signature::Ed25519(_) => "Ed25519",
+            Signature::Ecdsa(_) => "Ecdsa",
+            Signature::Ethereum(_) => "Ethereum",
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Signature::Sr25519(a) | Signature::Ed25519(a) => a,
+            Signature::Ecdsa(a) | Signature::Ethereum(a) => a,
+        }
+    }
+}
+
+/// Account identifier, either substrate-native or EVM-style
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountId {
+    Id32(H256),
+    Id20(H160),
+}
+
+impl AccountId {
+    /// SS58 for 32-byte accounts, EIP-55 checksummed hex for 20-byte accounts
+    pub fn name(&self, ss58: u16) -> String {
+        match self {
+            AccountId::Id32(a) => AccountId32(a.0).as_base58(ss58).to_string(),
+            AccountId::Id20(a) => eip55(a),
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        match self {
+            AccountId::Id32(a) => hex::encode(a.0),
+            AccountId::Id20(a) => hex::encode(a.0),
+        }
+    }
+}
+
+/// Mixed-case checksum encoding of 20-byte address
+pub fn eip55(address: &H160) -> String {
+    let lowercase = hex::encode(address.0);
+    let hash = keccak_256(lowercase.as_bytes());
+    let checksummed: String = lowercase
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+/// Read SS58 address, checking its checksum; gives account id and address prefix
+pub fn parse_ss58(input: &str) -> Result<(H256, u16), Error> {
+    match SpAccountId32::from_ss58check_with_version(input.trim()) {
+        Ok((account, format)) => {
+            let bytes: &[u8] = account.as_ref();
+            Ok((H256::from_slice(bytes), u16::from(format)))
+        }
+        Err(e) => Err(Error::InvalidAddress(e.to_string())),
+    }
+}
+
+/// Read SS58 address, or hex of 20-byte account
+pub fn parse_account(input: &str) -> Result<AccountId, Error> {
+    let input = input.trim();
+    match input.strip_prefix("0x").map(hex::decode) {
+        Some(Ok(a)) if a.len() == 20 => Ok(AccountId::Id20(H160::from_slice(&a))),
+        _ => parse_ss58(input).map(|(a, _)| AccountId::Id32(a)),
+    }
+}
+
+/// Full 64-byte sr25519 secret key, in hex; this is how imported keys are kept
+fn full_secret_key(input: &str) -> Option<Vec<u8>> {
+    match input.strip_prefix("0x").map(hex::decode) {
+        Some(Ok(a)) if a.len() == 64 => Some(a),
+        _ => None,
+    }
+}
+
+/// 20-byte address of secp256k1 key: last bytes of keccak hash of uncompressed public key
+pub fn ethereum_address(public: &ecdsa::Public) -> H160 {
+    let mut compressed = [0u8; 33];
+    compressed.copy_from_slice(public.as_ref());
+    let uncompressed = libsecp256k1::PublicKey::parse_compressed(&compressed)
+        .expect("public key of existing pair is valid; qed")
+        .serialize();
+    H160::from_slice(&keccak_256(&uncompressed[1..])[12..])
+}
+
+/// Path of first account in Ethereum wallets
+const ETHEREUM_PATH: &str = "m/44'/60'/0'/0/0";
+
+/// Offset of hardened BIP32 indices
+const HARDENED: u32 = 0x8000_0000;
+
+/// Ethereum key pair, as MetaMask or Moonbeam make it from the same phrase
+///
+/// Derivation string is phrase, then optional BIP32 path like `/m/44'/60'/0'/0/1`, then
+/// optional `///password` for BIP39 seed. Without path first account of wallet is made.
+/// Secret key in hex is taken as it is.
+fn ethereum_pair(full_address: &str) -> Result<ecdsa::Pair, String> {
+    let full_address = full_address.trim();
+    if full_address.starts_with("0x") {
+        return ecdsa::Pair::from_string(full_address, None).map_err(|e| e.to_string());
+    }
+    let (rest, password) = full_address.split_once("///").unwrap_or((full_address, ""));
+    let (phrase, path) = rest.split_once('/').unwrap_or((rest, ETHEREUM_PATH));
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase.trim())
+        .map_err(|e| e.to_string())?;
+    let secret = bip32_secret(&mnemonic.to_seed(password), path)?;
+    ecdsa::Pair::from_seed_slice(&secret).map_err(|e| e.to_string())
+}
+
+/// Secp256k1 secret key of BIP32 path from BIP39 seed
+fn bip32_secret(seed: &[u8], path: &str) -> Result<[u8; 32], String> {
+    let mut steps = path.trim().split('/');
+    if steps.next() != Some("m") {
+        return Err(format!("path {} does not start with m", path));
+    }
+    let (key, mut chain_code) = hmac_sha512(b"Bitcoin seed", seed);
+    let mut secret = libsecp256k1::SecretKey::parse(&key).map_err(|e| e.to_string())?;
+    for step in steps {
+        let index = match step.strip_suffix('\'').or_else(|| step.strip_suffix('h')) {
+            Some(a) => a
+                .parse::<u32>()
+                .ok()
+                .filter(|b| *b < HARDENED)
+                .map(|b| b + HARDENED),
+            None => step.parse::<u32>().ok().filter(|b| *b < HARDENED),
+        }
+        .ok_or(format!("invalid index {} in path {}", step, path))?;
+        let mut data = Vec::with_capacity(37);
+        if index >= HARDENED {
+            data.push(0);
+            data.extend_from_slice(&secret.serialize());
+        } else {
+            data.extend_from_slice(
+                &libsecp256k1::PublicKey::from_secret_key(&secret).serialize_compressed(),
+            );
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+        let (tweak, next_chain_code) = hmac_sha512(&chain_code, &data);
+        let tweak = libsecp256k1::SecretKey::parse(&tweak).map_err(|e| e.to_string())?;
+        secret.tweak_add_assign(&tweak).map_err(|e| e.to_string())?;
+        chain_code = next_chain_code;
+    }
+    Ok(secret.serialize())
+}
+
+/// Left and right halves of HMAC-SHA512
+fn hmac_sha512(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("any key length is accepted; qed");
+    mac.update(data);
+    let out = mac.finalize().into_bytes();
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&out[..32]);
+    right.copy_from_slice(&out[32..]);
+    (left, right)
 }
 
 pub enum Address {
     Public(H256),
-    Pair(sr25519::Pair),
+    Public20(H160),
+    Pair(KeyPair),
 }
 
 impl Address {
@@ -19,14 +264,59 @@ impl Address {
         Self::Public(public)
     }
 
-    pub fn from_public_hex(public: &str) -> Self {
-        Self::from_public(H256(hex::decode(public).unwrap().try_into().unwrap()))
+    /// Watch-only address from hex of 32-byte or 20-byte account id
+    pub fn from_public_hex(public: &str) -> Result<Self, Error> {
+        let invalid = |reason: String| Error::InvalidAddress(format!("{}: {}", public, reason));
+        let bytes =
+            hex::decode(public.trim_start_matches("0x")).map_err(|e| invalid(e.to_string()))?;
+        match bytes.len() {
+            20 => Ok(Self::Public20(H160::from_slice(&bytes))),
+            32 => Ok(Self::from_public(H256::from_slice(&bytes))),
+            a => Err(invalid(format!("account id of {} bytes", a))),
+        }
     }
 
-    pub fn from_derivation(full_address: &str) -> Result<Self, Error> {
-        match sr25519::Pair::from_string(full_address, None) {
+    /// Key pair from derivation string
+    ///
+    /// For sr25519 full secret key in hex is also accepted. Ethereum keys are derived as in
+    /// Ethereum wallets, see [`ethereum_pair`].
+    pub fn from_derivation(full_address: &str, crypto: Crypto) -> Result<Self, Error> {
+        let pair = match crypto {
+            Crypto::Sr25519 => match full_secret_key(full_address) {
+                Some(a) => sr25519::Pair::from_seed_slice(&a),
+                None => sr25519::Pair::from_string(full_address, None),
+            }
+            .map(KeyPair::Sr25519)
+            .map_err(|e| e.to_string()),
+            Crypto::Ed25519 => ed25519::Pair::from_string(full_address, None)
+                .map(KeyPair::Ed25519)
+                .map_err(|e| e.to_string()),
+            Crypto::Ecdsa => ecdsa::Pair::from_string(full_address, None)
+                .map(KeyPair::Ecdsa)
+                .map_err(|e| e.to_string()),
+            Crypto::Ethereum => ethereum_pair(full_address).map(KeyPair::Ethereum),
+        };
+        match pair {
             Ok(a) => Ok(Self::Pair(a)),
-            Err(e) => Err(Error::DerivationFailed(e.to_string())),
+            Err(e) => Err(Error::DerivationFailed(e)),
+        }
+    }
+
+    /// Watch-only address of soft derivation from this public key, assumed to be sr25519
+    ///
+    /// Hard junctions need secret key, so these are rejected.
+    pub fn derive_soft(&self, path: &str) -> Result<Self, Error> {
+        let public = match self {
+            Address::Public(a) => sr25519::Public::from_raw(a.0),
+            _ => return Err(Error::DerivationFailed("not a public key".to_string())),
+        };
+        let junctions = crypto::junctions(path.trim())
+            .ok_or(Error::DerivationFailed(format!("invalid path {}", path)))?;
+        match public.derive(junctions.into_iter()) {
+            Some(a) => Ok(Self::from_public(a.into())),
+            None => Err(Error::DerivationFailed(
+                "hard junctions need secret key".to_string(),
+            )),
         }
     }
 
@@ -38,90 +328,891 @@ impl Address {
 
     fn own_symbol(&self) -> String {
         match self {
-            Address::Public(_) => "-".to_string(),
+            Address::Public(_) | Address::Public20(_) => "-".to_string(),
             Address::Pair(_) => "+".to_string(),
         }
     }
 
-    pub fn public(&self) -> H256 {
+    /// Account id of address
+    ///
+    /// This is public key itself for sr25519 and ed25519, blake2 hash of compressed public key
+    /// for ecdsa, and 20 last bytes of keccak hash of uncompressed public key for ethereum.
+    pub fn account_id(&self) -> AccountId {
         match self {
-            Address::Public(a) => *a,
-            Address::Pair(a) => a.public().into(),
+            Address::Public(a) => AccountId::Id32(*a),
+            Address::Public20(a) => AccountId::Id20(*a),
+            Address::Pair(KeyPair::Sr25519(a)) => AccountId::Id32(a.public().into()),
+            Address::Pair(KeyPair::Ed25519(a)) => AccountId::Id32(a.public().into()),
+            Address::Pair(KeyPair::Ecdsa(a)) => {
+                AccountId::Id32(H256(blake2_256(a.public().as_ref())))
+            }
+            Address::Pair(KeyPair::Ethereum(a)) => AccountId::Id20(ethereum_address(&a.public())),
         }
     }
 
-    pub fn into_account_id32(&self) -> AccountId32 {
-        AccountId32(self.public().0)
+    /// 32-byte account id, if address has one
+    pub fn public(&self) -> Option<H256> {
+        match self.account_id() {
+            AccountId::Id32(a) => Some(a),
+            AccountId::Id20(_) => None,
+        }
+    }
+
+    /// 20-byte account id, if address has one
+    pub fn public20(&self) -> Option<H160> {
+        match self.account_id() {
+            AccountId::Id32(_) => None,
+            AccountId::Id20(a) => Some(a),
+        }
+    }
+
+    pub fn crypto(&self) -> Option<Crypto> {
+        match self {
+            Address::Public(_) | Address::Public20(_) => None,
+            Address::Pair(KeyPair::Sr25519(_)) => Some(Crypto::Sr25519),
+            Address::Pair(KeyPair::Ed25519(_)) => Some(Crypto::Ed25519),
+            Address::Pair(KeyPair::Ecdsa(_)) => Some(Crypto::Ecdsa),
+            Address::Pair(KeyPair::Ethereum(_)) => Some(Crypto::Ethereum),
+        }
+    }
+
+    pub fn into_account_id32(&self) -> Option<AccountId32> {
+        self.public().map(|a| AccountId32(a.0))
     }
 
     pub fn name(&self, ss58: u16) -> String {
-        format!(
-            "[{}] {}",
-            self.own_symbol(),
-            self.into_account_id32().as_base58(ss58).to_string()
-        )
+        format!("[{}] {}", self.own_symbol(), self.account_id().name(ss58))
     }
 
-    pub fn sign(&self, input: &[u8]) -> Option<sr25519::Signature> {
+    pub fn sign(&self, input: &[u8]) -> Option<Signature> {
         match self {
-            Address::Public(_) => None,
-            Address::Pair(a) => Some(a.sign(input)),
+            Address::Public(_) | Address::Public20(_) => None,
+            Address::Pair(KeyPair::Sr25519(a)) => Some(Signature::Sr25519(a.sign(input).0)),
+            Address::Pair(KeyPair::Ed25519(a)) => Some(Signature::Ed25519(a.sign(input).0)),
+            Address::Pair(KeyPair::Ecdsa(a)) => Some(Signature::Ecdsa(a.sign(input).0)),
+            Address::Pair(KeyPair::Ethereum(a)) => {
+                Some(Signature::Ethereum(a.sign_prehashed(&keccak_256(input)).0))
+            }
+        }
+    }
+}
+
+/// Generate new english mnemonic phrase of 12 or 24 words
+pub fn generate_mnemonic(words: usize) -> Result<String, Error> {
+    match Mnemonic::generate_in(Language::English, words) {
+        Ok(a) => Ok(a.to_string()),
+        Err(e) => Err(Error::InvalidMnemonic(e.to_string())),
+    }
+}
+
+/// Check mnemonic phrase and bring it to canonical form
+pub fn normalize_mnemonic(phrase: &str) -> Result<String, Error> {
+    match Mnemonic::parse_in_normalized(Language::English, &phrase.to_lowercase()) {
+        Ok(a) => Ok(a.to_string()),
+        Err(e) => Err(Error::InvalidMnemonic(e.to_string())),
+    }
+}
+
+/// Address with its human-readable name
+pub struct Account {
+    pub address: Address,
+    pub label: Option<String>,
+}
+
+impl Account {
+    fn new(address: Address, label: Option<&str>) -> Self {
+        Self {
+            address,
+            label: label.map(|a| a.to_string()),
         }
     }
+
+    fn dev(full_address: &str, crypto: Crypto) -> Self {
+        let label = match crypto {
+            Crypto::Sr25519 => format!("dev {}", full_address),
+            _ => format!("dev {} {:?}", full_address, crypto),
+        };
+        Self::new(
+            Address::from_derivation(full_address, crypto).unwrap(),
+            Some(&label),
+        )
+    }
+
+    fn from_entry(entry: &Entry) -> Result<Self, keystore::Error> {
+        Address::from_public_hex(&entry.public)
+            .map(|a| Self::new(a, entry.label.as_deref()))
+            .map_err(|e| keystore::Error::Format(format!("{:?}", e)))
+    }
+
+    pub fn name(&self, ss58: u16) -> String {
+        labelled(self.address.name(ss58), self.label.as_deref())
+    }
+}
+
+/// Account name followed by its label, the same way everywhere
+pub fn labelled(name: String, label: Option<&str>) -> String {
+    match label {
+        Some(a) => format!("{} ({})", name, a),
+        None => name,
+    }
+}
+
+/// Multisig account, labelled with threshold
+fn multisig_account(multisig: &Multisig, label: Option<&str>) -> Account {
+    let threshold = format!(
+        "multisig {}/{}",
+        multisig.threshold(),
+        multisig.signatories().len()
+    );
+    let label = match label {
+        Some(a) => format!("{} {}", threshold, a),
+        None => threshold,
+    };
+    Account::new(Address::from_public(multisig.account_id()), Some(&label))
 }
 
 pub struct AddressBook {
-    content: Vec<Address>,
+    /// Own accounts: development ones first, then keystore ones
+    content: Vec<Account>,
+    /// Recipients not controlled by user, and multisig accounts
+    contacts: Vec<Account>,
+    multisigs: Vec<Multisig>,
+    /// Number of built-in development accounts, these go before keystore accounts
+    dev: usize,
+    keystore: Keystore,
     ss58: u16,
+    log: Vec<String>,
 }
 
 impl AddressBook {
-    pub fn init(ss58: u16) -> Self {
+    /// Address book with accounts of keystore, and development accounts if asked
+    ///
+    /// Keystore with damaged account ids is rejected as a whole.
+    pub fn init(
+        ss58: u16,
+        keystore: Keystore,
+        dev_accounts: bool,
+    ) -> Result<Self, keystore::Error> {
         let mut content = Vec::new();
-        // Debuggers built-in addresses
-        content.push(Address::from_derivation("//Kampela").unwrap());
-        content.push(Address::from_derivation("//westend//Kampela").unwrap());
-        content.push(Address::from_derivation("").unwrap());
-        content.push(Address::from_derivation("//polkadot").unwrap());
-        content.push(Address::from_derivation("//polkadot//Alice").unwrap());
-        content.push(Address::from_public_hex(
-            "be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f",
-        ));
-        content.push(Address::from_derivation("//Alice").unwrap());
-        content.push(Address::from_public_hex(
-            "fe65717dad0447d715f660a0a58411de509b42e6efb8375f562f58a554d5860e",
-        ));
-        content.push(Address::from_derivation("//Bob").unwrap());
-        content.push(Address::from_derivation("//Charlie").unwrap());
-        content.push(Address::from_public_hex(
-            "306721211d5404bd9da88e0204360a1a9ab8b87c66c1bc2fcdd37f3c2222cc20",
-        ));
-        content.push(Address::from_derivation("//Fred").unwrap());
-        Self { content, ss58 }
+        if dev_accounts {
+            // Debuggers built-in addresses
+            content.push(Account::dev("//Kampela", Crypto::Sr25519));
+            content.push(Account::dev("//westend//Kampela", Crypto::Sr25519));
+            content.push(Account::dev("", Crypto::Sr25519));
+            content.push(Account::dev("//polkadot", Crypto::Sr25519));
+            content.push(Account::dev("//polkadot//Alice", Crypto::Sr25519));
+            content.push(Account::new(
+                Address::from_public_hex(
+                    "be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f",
+                )
+                .unwrap(),
+                None,
+            ));
+            content.push(Account::dev("//Alice", Crypto::Sr25519));
+            content.push(Account::new(
+                Address::from_public_hex(
+                    "fe65717dad0447d715f660a0a58411de509b42e6efb8375f562f58a554d5860e",
+                )
+                .unwrap(),
+                None,
+            ));
+            content.push(Account::dev("//Bob", Crypto::Sr25519));
+            content.push(Account::dev("//Charlie", Crypto::Sr25519));
+            content.push(Account::new(
+                Address::from_public_hex(
+                    "306721211d5404bd9da88e0204360a1a9ab8b87c66c1bc2fcdd37f3c2222cc20",
+                )
+                .unwrap(),
+                None,
+            ));
+            content.push(Account::dev("//Fred", Crypto::Sr25519));
+            content.push(Account::dev("//Alice", Crypto::Ed25519));
+            content.push(Account::dev("//Alice", Crypto::Ecdsa));
+            // Alith, well-known EVM development account
+            content.push(Account::new(
+                Address::from_derivation(
+                    "0x5fb92d6e98884f76de468fa3f6278f8807c48bebc13595d45af5bdc4da702133",
+                    Crypto::Ethereum,
+                )
+                .unwrap(),
+                Some("dev Alith"),
+            ));
+        }
+        let dev = content.len();
+        // Keystore accounts start locked
+        for entry in &keystore.entries {
+            content.push(Account::from_entry(entry)?);
+        }
+        let mut contacts = keystore
+            .contacts
+            .iter()
+            .map(Account::from_entry)
+            .collect::<Result<Vec<Account>, _>>()?;
+        let mut multisigs = Vec::new();
+        let mut log = Vec::new();
+        for entry in &keystore.multisigs {
+            match Multisig::from_entry(entry) {
+                Ok(a) => {
+                    contacts.push(multisig_account(&a, entry.label.as_deref()));
+                    multisigs.push(a);
+                }
+                Err(e) => log.push(format!("multisig not loaded: {}", e)),
+            }
+        }
+        Ok(Self {
+            content,
+            contacts,
+            multisigs,
+            dev,
+            keystore,
+            ss58,
+            log,
+        })
     }
 
-    pub fn authors(&self) -> &Vec<Address> {
+    pub fn authors(&self) -> &Vec<Account> {
         &self.content
     }
 
+    pub fn contacts(&self) -> &Vec<Account> {
+        &self.contacts
+    }
+
+    /// Own accounts and then contacts, in the order of indices used in selectors
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.content.iter().chain(self.contacts.iter())
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Account> {
+        self.accounts().nth(index)
+    }
+
     pub fn author_names(&self) -> Vec<String> {
+        self.authors().iter().map(|a| a.name(self.ss58)).collect()
+    }
+
+    /// Names of own accounts and contacts
+    pub fn names(&self) -> Vec<String> {
         self.authors()
             .iter()
-            .map(|a| format!("{}", a.name(self.ss58)))
+            .map(|a| a.name(self.ss58))
+            .chain(
+                self.contacts()
+                    .iter()
+                    .map(|a| format!("[@]{}", a.name(self.ss58).trim_start_matches("[-]"))),
+            )
             .collect()
     }
 
+    /// Label of known account, own or contact
+    pub fn label_of(&self, account_id: &AccountId) -> Option<&str> {
+        self.accounts()
+            .find(|a| a.address.account_id() == *account_id)
+            .and_then(|a| a.label.as_deref())
+    }
+
+    /// Crypto of locked or watch-only key, as recorded in keystore; sr25519 if there is no record
+    pub fn crypto(&self, account_id: &AccountId) -> Crypto {
+        let public = account_id.to_hex();
+        self.keystore
+            .entries
+            .iter()
+            .find(|a| a.public == public)
+            .map(|a| a.crypto)
+            .unwrap_or_default()
+    }
+
+    pub fn ss58(&self) -> u16 {
+        self.ss58
+    }
+
     pub fn public(&self, index: usize) -> Option<H256> {
-        match self.authors().get(index) {
-            Some(a) => Some(a.public()),
+        match self.get(index) {
+            Some(a) => a.address.public(),
             None => None,
         }
     }
 
     pub fn account_id32(&self, index: usize) -> Option<AccountId32> {
-        match self.authors().get(index) {
-            Some(a) => Some(a.into_account_id32()),
+        match self.get(index) {
+            Some(a) => a.address.into_account_id32(),
             None => None,
         }
     }
+
+    pub fn account_id20(&self, index: usize) -> Option<H160> {
+        match self.get(index) {
+            Some(a) => a.address.public20(),
+            None => None,
+        }
+    }
+
+    /// Known multisig with this account id
+    pub fn multisig(&self, account_id: &H256) -> Option<&Multisig> {
+        self.multisigs
+            .iter()
+            .find(|a| a.account_id() == *account_id)
+    }
+
+    /// Find own address by its account id
+    pub fn find(&self, account_id: &AccountId) -> Option<&Address> {
+        self.authors()
+            .iter()
+            .map(|a| &a.address)
+            .find(|a| a.account_id() == *account_id)
+    }
+
+    /// Own account by label, or by SS58 address or 20-byte hex
+    pub fn find_author(&self, name: &str) -> Option<AccountId> {
+        self.authors()
+            .iter()
+            .find(|a| a.label.as_deref() == Some(name.trim()))
+            .map(|a| a.address.account_id())
+            .or_else(|| parse_account(name).ok().filter(|a| self.find(a).is_some()))
+    }
+
+    /// Whether any keystore secret is currently decrypted
+    pub fn is_unlocked(&self) -> bool {
+        self.content[self.dev..]
+            .iter()
+            .any(|a| matches!(a.address, Address::Pair(_)))
+    }
+
+    /// Decrypt all keystore secrets sealed with this password
+    ///
+    /// Secrets sealed with other passwords remain locked.
+    pub fn unlock(&mut self, password: &str) -> usize {
+        let mut unlocked = 0;
+        for (index, entry) in self.keystore.entries.iter().enumerate() {
+            if let Some(ref secret) = entry.secret {
+                match unseal(entry, secret, password) {
+                    Ok(a) => {
+                        self.content[self.dev + index].address = a;
+                        unlocked += 1;
+                    }
+                    Err(Error::Keystore(keystore::Error::DecryptionFailed)) => {}
+                    Err(e) => self.log.push(format!("entry {}: {:?}", entry.public, e)),
+                }
+            }
+        }
+        self.log.push(format!("{} keys unlocked", unlocked));
+        unlocked
+    }
+
+    /// Forget all decrypted keystore secrets
+    pub fn lock(&mut self) {
+        for (index, entry) in self.keystore.entries.iter().enumerate() {
+            self.content[self.dev + index].address = Address::from_public_hex(&entry.public)
+                .expect("entries are checked when address book is made; qed");
+        }
+        self.log.push("keystore locked".to_string());
+    }
+
+    /// Add public key that could not sign, persistently
+    pub fn add_watch_only(&mut self, public: H256, label: Option<String>) -> Result<(), Error> {
+        if self.find(&AccountId::Id32(public)).is_some() {
+            self.log.push("account is already known".to_string());
+            return Ok(());
+        }
+        let entry = Entry {
+            public: hex::encode(public.0),
+            label,
+            crypto: Crypto::default(),
+            secret: None,
+        };
+        self.keystore.push(entry.clone()).map_err(Error::Keystore)?;
+        let account = Account::from_entry(&entry).map_err(Error::Keystore)?;
+        self.log
+            .push(format!("added watch-only {}", account.name(self.ss58)));
+        self.content.push(account);
+        Ok(())
+    }
+
+    /// Add watch-only child of own sr25519 public key, derived along soft path, persistently
+    ///
+    /// Child is labelled with path after label of parent, unless label is given.
+    pub fn add_derived_watch_only(
+        &mut self,
+        parent: H256,
+        path: &str,
+        label: Option<String>,
+    ) -> Result<(), Error> {
+        let not_derivable = || Error::NotDerivable(Address::from_public(parent).name(self.ss58));
+        let account = self
+            .authors()
+            .iter()
+            .find(|a| matches!(a.address, Address::Public(b) if b == parent))
+            .ok_or_else(not_derivable)?;
+        let public = hex::encode(parent.0);
+        if self
+            .keystore
+            .entries
+            .iter()
+            .any(|a| a.public == public && a.crypto != Crypto::Sr25519)
+        {
+            return Err(not_derivable());
+        }
+        let child = account
+            .address
+            .derive_soft(path)?
+            .public()
+            .ok_or_else(not_derivable)?;
+        let label = label.or_else(|| {
+            account
+                .label
+                .as_ref()
+                .map(|a| format!("{}{}", a, path.trim()))
+        });
+        self.add_watch_only(child, label)
+    }
+
+    /// Add recipient, persistently
+    pub fn add_contact(&mut self, public: H256, label: String) -> Result<(), Error> {
+        if self
+            .accounts()
+            .any(|a| a.address.account_id() == AccountId::Id32(public))
+        {
+            self.log.push("account is already known".to_string());
+            return Ok(());
+        }
+        let entry = Entry {
+            public: hex::encode(public.0),
+            label: Some(label),
+            crypto: Crypto::default(),
+            secret: None,
+        };
+        self.keystore
+            .push_contact(entry.clone())
+            .map_err(Error::Keystore)?;
+        let account = Account::from_entry(&entry).map_err(Error::Keystore)?;
+        self.log
+            .push(format!("added contact {}", account.name(self.ss58)));
+        self.contacts.push(account);
+        Ok(())
+    }
+
+    /// Add multisig, persistently; its account goes to contacts
+    pub fn add_multisig(
+        &mut self,
+        signatories: Vec<H256>,
+        threshold: u16,
+        label: Option<String>,
+    ) -> Result<(), Error> {
+        let multisig = Multisig::new(signatories, threshold).map_err(Error::Multisig)?;
+        if self.multisig(&multisig.account_id()).is_some() {
+            self.log.push("multisig is already known".to_string());
+            return Ok(());
+        }
+        self.keystore
+            .push_multisig(multisig.to_entry(label.clone()))
+            .map_err(Error::Keystore)?;
+        let account = multisig_account(&multisig, label.as_deref());
+        self.log
+            .push(format!("added multisig {}", account.name(self.ss58)));
+        self.contacts.push(account);
+        self.multisigs.push(multisig);
+        Ok(())
+    }
+
+    /// Add key from derivation string, persistently, with secret sealed by password
+    pub fn add_secret(
+        &mut self,
+        full_address: &str,
+        crypto: Crypto,
+        label: Option<String>,
+        password: &str,
+    ) -> Result<(), Error> {
+        let address = Address::from_derivation(full_address, crypto)?;
+        let secret = EncryptedSecret::seal(full_address, password).map_err(Error::Keystore)?;
+        self.keystore
+            .push(Entry {
+                public: address.account_id().to_hex(),
+                label: label.clone(),
+                crypto,
+                secret: Some(secret),
+            })
+            .map_err(Error::Keystore)?;
+        let account = Account { address, label };
+        self.log.push(format!("added {}", account.name(self.ss58)));
+        self.content.push(account);
+        Ok(())
+    }
+
+    /// Add key from polkadot-js JSON backup, persistently, with secret sealed by password
+    ///
+    /// Backup password only opens the backup; keystore uses its own password.
+    pub fn import_json(
+        &mut self,
+        path: &str,
+        json_password: &str,
+        password: &str,
+    ) -> Result<(), Error> {
+        let json = polkadot_js::Json::read(path).map_err(Error::PolkadotJs)?;
+        let decoded = json.decrypt(json_password).map_err(Error::PolkadotJs)?;
+        let secret = decoded.secret_string().map_err(Error::PolkadotJs)?;
+        let address = Address::from_derivation(&secret, decoded.crypto)?;
+        if parse_account(&json.address)? != address.account_id() {
+            return Err(Error::PublicMismatch(json.address));
+        }
+        self.add_secret(&secret, decoded.crypto, json.name(), password)
+    }
+
+    /// Sign message wrapped in `<Bytes>...</Bytes>` with unlocked own key
+    pub fn sign_message(&self, account_id: &AccountId, message: &[u8]) -> Result<Signature, Error> {
+        match self.find(account_id) {
+            Some(a) => a
+                .sign(&wrap_bytes(message))
+                .ok_or(Error::NotUnlocked(account_id.name(self.ss58))),
+            None => Err(Error::InvalidAddress(account_id.name(self.ss58))),
+        }
+    }
+
+    /// VRF signature over transcript with unlocked own sr25519 key
+    pub fn vrf_sign(
+        &self,
+        account_id: &AccountId,
+        data: &VrfSignData,
+    ) -> Result<VrfSignature, Error> {
+        match self.find(account_id) {
+            Some(Address::Pair(KeyPair::Sr25519(a))) => Ok(a.vrf_sign(data)),
+            Some(Address::Pair(_)) => Err(Error::WrongCrypto(account_id.name(self.ss58))),
+            Some(_) => Err(Error::NotUnlocked(account_id.name(self.ss58))),
+            None => Err(Error::InvalidAddress(account_id.name(self.ss58))),
+        }
+    }
+
+    /// Write unlocked sr25519 or ed25519 key into polkadot-js JSON backup
+    pub fn export_json(
+        &mut self,
+        account_id: &AccountId,
+        path: &str,
+        password: &str,
+    ) -> Result<(), Error> {
+        let account = self
+            .authors()
+            .iter()
+            .find(|a| a.address.account_id() == *account_id)
+            .ok_or(Error::InvalidAddress(account_id.name(self.ss58)))?;
+        let pair = match account.address {
+            Address::Pair(ref a) => a,
+            _ => return Err(Error::NotUnlocked(account_id.name(self.ss58))),
+        };
+        polkadot_js::Json::encrypt(
+            pair,
+            account_id.name(self.ss58),
+            account.label.clone(),
+            password,
+        )
+        .and_then(|a| a.save(path))
+        .map_err(Error::PolkadotJs)?;
+        self.log
+            .push(format!("exported {} to {}", account.name(self.ss58), path));
+        Ok(())
+    }
+
+    pub fn log(&mut self) -> String {
+        let mut out = String::new();
+        while let Some(a) = self.log.pop() {
+            out += "keys: ";
+            out += &a;
+            out += "\r\n";
+        }
+        out
+    }
+}
+
+fn unseal(entry: &Entry, secret: &EncryptedSecret, password: &str) -> Result<Address, Error> {
+    let full_address = secret.open(password).map_err(Error::Keystore)?;
+    let address = Address::from_derivation(&full_address, entry.crypto)?;
+    if address.account_id().to_hex() != entry.public {
+        return Err(Error::PublicMismatch(entry.public.to_owned()));
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::extrinsic_builder::Selector;
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+    /// Address book over keystore in temporary directory, with no file there yet
+    fn address_book(name: &str) -> AddressBook {
+        let path = std::env::temp_dir().join(format!(
+            "lempi-address-book-{}-{}.json",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        let keystore = Keystore::open(path.to_str().unwrap()).unwrap();
+        AddressBook::init(42, keystore, false).unwrap()
+    }
+
+    fn ethereum(full_address: &str) -> String {
+        match Address::from_derivation(full_address, Crypto::Ethereum)
+            .unwrap()
+            .account_id()
+        {
+            AccountId::Id20(a) => eip55(&a),
+            AccountId::Id32(_) => panic!("ethereum account is 20 bytes"),
+        }
+    }
+
+    #[test]
+    fn ethereum_wallet_accounts() {
+        let phrase = "test test test test test test test test test test test junk";
+        assert_eq!(
+            ethereum(phrase),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+        );
+        assert_eq!(
+            ethereum(&format!("{}/m/44'/60'/0'/0/0", phrase)),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+        );
+        assert_eq!(
+            ethereum(&format!("{}/m/44'/60'/0'/0/1", phrase)),
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+        );
+    }
+
+    #[test]
+    fn moonbeam_alith() {
+        let alith = "0xf24FF3a9CF04c71Dbc94D0b566f7A27B94566cac";
+        assert_eq!(
+            ethereum("bottom drive obey lake curtain smoke basket hold race lonely fit walk"),
+            alith
+        );
+        assert_eq!(
+            ethereum("0x5fb92d6e98884f76de468fa3f6278f8807c48bebc13595d45af5bdc4da702133"),
+            alith
+        );
+    }
+
+    #[test]
+    fn ethereum_invalid_paths() {
+        let phrase = "test test test test test test test test test test test junk";
+        for path in ["/44'/60'", "/m/x", "/m/2147483648"] {
+            assert!(
+                Address::from_derivation(&format!("{}{}", phrase, path), Crypto::Ethereum).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn ss58_checksum_and_prefix() {
+        let (alice, prefix) = parse_ss58(ALICE).unwrap();
+        assert_eq!(
+            hex::encode(alice),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+        assert_eq!(prefix, 42);
+        // same key with Polkadot prefix
+        assert_eq!(
+            parse_ss58("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5").unwrap(),
+            (alice, 0)
+        );
+        // last character changed
+        assert!(matches!(
+            parse_ss58("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQZ"),
+            Err(Error::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn watch_only_added_once() {
+        let mut address_book = address_book("watch-only");
+        let (alice, _) = parse_ss58(ALICE).unwrap();
+        address_book
+            .add_watch_only(alice, Some("alice".to_string()))
+            .unwrap();
+        address_book.add_watch_only(alice, None).unwrap();
+        assert_eq!(address_book.authors().len(), 1);
+        assert_eq!(address_book.keystore.entries.len(), 1);
+        assert_eq!(address_book.log.last().unwrap(), "account is already known");
+        assert!(matches!(
+            address_book.find(&AccountId::Id32(alice)),
+            Some(Address::Public(_))
+        ));
+    }
+
+    #[test]
+    fn contact_added_once() {
+        let mut address_book = address_book("contact");
+        let (alice, _) = parse_ss58(ALICE).unwrap();
+        address_book
+            .add_contact(alice, "alice".to_string())
+            .unwrap();
+        address_book
+            .add_contact(alice, "Alice".to_string())
+            .unwrap();
+        assert_eq!(address_book.contacts().len(), 1);
+        assert_eq!(address_book.keystore.contacts.len(), 1);
+        assert_eq!(address_book.log.last().unwrap(), "account is already known");
+        assert_eq!(
+            address_book.label_of(&AccountId::Id32(alice)),
+            Some("alice")
+        );
+
+        // own account is not a contact
+        let (bob, _) = parse_ss58(BOB).unwrap();
+        address_book.add_watch_only(bob, None).unwrap();
+        address_book.add_contact(bob, "bob".to_string()).unwrap();
+        assert_eq!(address_book.contacts().len(), 1);
+    }
+
+    #[test]
+    fn selector_filters_labels_and_addresses() {
+        let mut address_book = address_book("selector");
+        let (alice, _) = parse_ss58(ALICE).unwrap();
+        let (bob, _) = parse_ss58(BOB).unwrap();
+        address_book
+            .add_watch_only(alice, Some("alice".to_string()))
+            .unwrap();
+        address_book
+            .add_contact(bob, "Bob shop".to_string())
+            .unwrap();
+        let mut selector = Selector::new(address_book.names(), 0);
+
+        "shop".chars().for_each(|c| selector.filter_push(c));
+        assert_eq!(selector.visible().count(), 1);
+        assert_eq!(
+            address_book.get(selector.index()).unwrap().address.public(),
+            Some(bob)
+        );
+
+        (0..4).for_each(|_| selector.filter_pop());
+        "5grw".chars().for_each(|c| selector.filter_push(c));
+        assert_eq!(selector.visible().count(), 1);
+        assert_eq!(
+            address_book.get(selector.index()).unwrap().address.public(),
+            Some(alice)
+        );
+
+        selector.filter_push('!');
+        assert!(selector.is_empty());
+    }
+
+    #[test]
+    fn soft_derived_watch_only_matches_pair() {
+        let mut address_book = address_book("derive");
+        let parent = Address::from_derivation("//Alice", Crypto::Sr25519)
+            .unwrap()
+            .public()
+            .unwrap();
+        address_book
+            .add_watch_only(parent, Some("alice".to_string()))
+            .unwrap();
+        address_book
+            .add_derived_watch_only(parent, "/savings/1", None)
+            .unwrap();
+
+        let pair = Address::from_derivation("//Alice/savings/1", Crypto::Sr25519).unwrap();
+        let child = address_book.authors().last().unwrap();
+        assert!(matches!(child.address, Address::Public(_)));
+        assert_eq!(child.address.public(), pair.public());
+        assert_eq!(child.label.as_deref(), Some("alice/savings/1"));
+
+        // secret is needed for hard junctions
+        assert!(address_book
+            .add_derived_watch_only(parent, "//hard", None)
+            .is_err());
+        assert_eq!(address_book.authors().len(), 2);
+    }
+
+    #[test]
+    fn bip39_phrases() {
+        let abandon = format!("{} about", "abandon ".repeat(11).trim());
+        assert_eq!(
+            Mnemonic::from_entropy_in(Language::English, &[0; 16])
+                .unwrap()
+                .to_string(),
+            abandon
+        );
+        assert_eq!(
+            normalize_mnemonic(&format!("  {}  ", abandon.to_uppercase())).unwrap(),
+            abandon
+        );
+
+        // checksum of twelve `abandon` does not match
+        let bad_checksum = "abandon ".repeat(12);
+        assert!(matches!(
+            normalize_mnemonic(&bad_checksum),
+            Err(Error::InvalidMnemonic(_))
+        ));
+        let eleven_words = "abandon ".repeat(11);
+        assert!(normalize_mnemonic(&eleven_words).is_err());
+        assert!(normalize_mnemonic(&abandon.replace("about", "aboot")).is_err());
+
+        for words in [12, 24] {
+            let phrase = generate_mnemonic(words).unwrap();
+            assert_eq!(phrase.split(' ').count(), words);
+            assert_eq!(normalize_mnemonic(&phrase).unwrap(), phrase);
+        }
+        assert!(generate_mnemonic(13).is_err());
+    }
+
+    #[test]
+    fn derivation_from_phrase() {
+        let dev_phrase = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+        let sr25519 = |full_address: &str| {
+            Address::from_derivation(full_address, Crypto::Sr25519)
+                .unwrap()
+                .account_id()
+                .name(42)
+        };
+        assert_eq!(sr25519("//Alice"), ALICE);
+        assert_eq!(sr25519(&format!("{}//Alice", dev_phrase)), ALICE);
+        assert_eq!(sr25519(&format!("{}//Bob", dev_phrase)), BOB);
+        assert_eq!(
+            sr25519(dev_phrase),
+            "5DfhGyQdFobKM8NsWvEeAKk5EQQgYe9AydgJ7rMB6E1EqRzV"
+        );
+        // password changes the key
+        assert_ne!(sr25519("//Alice///secret"), ALICE);
+        assert!(Address::from_derivation("//Alice/", Crypto::Sr25519).is_err());
+    }
+
+    /// Check signature with public key of address, the way chain checks `MultiSignature`
+    fn verified(address: &Address, signature: &Signature, payload: &[u8]) -> bool {
+        let public = address.public().unwrap();
+        match signature {
+            Signature::Sr25519(a) => sr25519::Pair::verify(
+                &sr25519::Signature::from_raw(*a),
+                payload,
+                &sr25519::Public::from_raw(public.0),
+            ),
+            Signature::Ed25519(a) => ed25519::Pair::verify(
+                &ed25519::Signature::from_raw(*a),
+                payload,
+                &ed25519::Public::from_raw(public.0),
+            ),
+            // ecdsa account id is hash of public key
+            Signature::Ecdsa(a) => ecdsa::Signature::from_raw(*a)
+                .recover(payload)
+                .map(|b| blake2_256(b.as_ref()) == public.0)
+                .unwrap_or(false),
+            Signature::Ethereum(_) => false,
+        }
+    }
+
+    #[test]
+    fn signatures_in_matching_variant() {
+        let payload = b"signable payload";
+        for (crypto, variant) in [
+            (Crypto::Sr25519, "Sr25519"),
+            (Crypto::Ed25519, "Ed25519"),
+            (Crypto::Ecdsa, "Ecdsa"),
+        ] {
+            let address = Address::from_derivation("//Alice", crypto).unwrap();
+            let signature = address.sign(payload).unwrap();
+            assert_eq!(signature.variant_name(), variant);
+            assert!(verified(&address, &signature, payload), "{:?}", crypto);
+            assert!(!verified(&address, &signature, b"other payload"));
+            let bob = Address::from_derivation("//Bob", crypto).unwrap();
+            assert!(!verified(&bob, &signature, payload));
+        }
+    }
 }
@@ -0,0 +1,231 @@
+use frame_metadata::v15::RuntimeMetadataV15;
+
+use parity_scale_codec::Decode;
+
+use primitive_types::H256;
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::author::parse_ss58;
+use crate::units::Units;
+
+#[derive(Debug)]
+pub enum Error {
+    Empty,
+    Io(std::io::Error),
+    /// Line number and reason, for every bad row
+    Rows(Vec<(usize, String)>),
+    TotalOverflow,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Empty => write!(f, "no transfers in file"),
+            Error::Io(a) => write!(f, "transfers file: {}", a),
+            Error::Rows(a) => {
+                write!(f, "{} rows are invalid:", a.len())?;
+                for (line, reason) in a {
+                    write!(f, "\r\nline {}: {}", line, reason)?;
+                }
+                Ok(())
+            }
+            Error::TotalOverflow => write!(f, "total does not fit into u128"),
+        }
+    }
+}
+
+/// Single row of transfers file
+pub struct Transfer {
+    pub dest: H256,
+    /// Planck
+    pub amount: u128,
+    pub memo: Option<String>,
+}
+
+/// Transfers read from file, with their total
+pub struct Transfers {
+    pub transfers: Vec<Transfer>,
+    /// Planck
+    pub total: u128,
+    /// Line number and reason, for rows that are valid but likely mistaken
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// Read `address,amount[,memo]` rows; amounts are in chain units, as typed in balance fields
+///
+/// Every row is checked, and all bad rows are reported together. Header row is skipped if
+/// it starts with `address`; memo may contain commas. Repeated recipients, and amounts below
+/// existential deposit, which fail for new accounts, are warned about.
+pub fn read_transfers(
+    file: &str,
+    units: &Units,
+    ss58: u16,
+    existential_deposit: Option<u128>,
+) -> Result<Transfers, Error> {
+    let text = fs::read_to_string(file).map_err(Error::Io)?;
+    parse_transfers(&text, units, ss58, existential_deposit)
+}
+
+fn parse_transfers(
+    text: &str,
+    units: &Units,
+    ss58: u16,
+    existential_deposit: Option<u128>,
+) -> Result<Transfers, Error> {
+    let mut transfers = Vec::new();
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut recipients = HashMap::new();
+    for (index, row) in text.lines().enumerate() {
+        let row = row.trim();
+        if row.is_empty() || (index == 0 && row.to_lowercase().starts_with("address")) {
+            continue;
+        }
+        match transfer(row, units, ss58) {
+            Ok(a) => {
+                if let Some(line) = recipients.insert(a.dest, index + 1) {
+                    warnings.push((index + 1, format!("recipient is also on line {}", line)));
+                }
+                match existential_deposit {
+                    Some(b) if a.amount < b => warnings.push((
+                        index + 1,
+                        format!(
+                            "amount is below existential deposit {}, transfer to new account fails",
+                            units.format(b)
+                        ),
+                    )),
+                    _ => {}
+                }
+                transfers.push(a)
+            }
+            Err(e) => errors.push((index + 1, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(Error::Rows(errors));
+    }
+    if transfers.is_empty() {
+        return Err(Error::Empty);
+    }
+    let total = transfers
+        .iter()
+        .try_fold(0u128, |total, a| total.checked_add(a.amount))
+        .ok_or(Error::TotalOverflow)?;
+    Ok(Transfers {
+        transfers,
+        total,
+        warnings,
+    })
+}
+
+/// `Balances` constant `ExistentialDeposit`, in planck
+pub fn existential_deposit(metadata: &RuntimeMetadataV15) -> Option<u128> {
+    let value = &metadata
+        .pallets
+        .iter()
+        .find(|a| a.name == "Balances")?
+        .constants
+        .iter()
+        .find(|a| a.name == "ExistentialDeposit")?
+        .value;
+    match value.len() {
+        16 => u128::decode(&mut &value[..]).ok(),
+        8 => u64::decode(&mut &value[..]).ok().map(u128::from),
+        4 => u32::decode(&mut &value[..]).ok().map(u128::from),
+        _ => None,
+    }
+}
+
+fn transfer(row: &str, units: &Units, ss58: u16) -> Result<Transfer, String> {
+    let mut parts = row.splitn(3, ',').map(str::trim);
+    let address = parts.next().unwrap_or_default();
+    let amount = parts.next().ok_or("amount is missing".to_string())?;
+    let memo = parts.next().filter(|a| !a.is_empty()).map(str::to_string);
+    let dest = match parse_ss58(address) {
+        Ok((_, prefix)) if prefix != ss58 => {
+            return Err(format!(
+                "address {} is for prefix {}, chain uses {}",
+                address, prefix, ss58
+            ))
+        }
+        Ok((a, _)) => a,
+        Err(e) => return Err(format!("invalid address {}: {:?}", address, e)),
+    };
+    let amount = units
+        .parse(amount)
+        .map_err(|e| format!("invalid amount {}: {}", amount, e))?;
+    if amount == 0 {
+        return Err("amount is zero".to_string());
+    }
+    Ok(Transfer { dest, amount, memo })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sp_core::crypto::{AccountId32, Ss58Codec};
+
+    fn address(byte: u8) -> String {
+        AccountId32::from([byte; 32]).to_ss58check_with_version(42u16.into())
+    }
+
+    #[test]
+    fn header_is_skipped_and_memo_keeps_commas() {
+        let text = format!(
+            "Address,Amount,Memo\r\n{},5,rent, march\r\n\r\n{},7\r\n",
+            address(1),
+            address(2)
+        );
+        let read = parse_transfers(&text, &Units::planck(), 42, None).unwrap();
+        assert_eq!(read.total, 12);
+        assert_eq!(read.transfers.len(), 2);
+        assert_eq!(read.transfers[0].memo.as_deref(), Some("rent, march"));
+        assert_eq!(read.transfers[1].memo, None);
+        assert!(read.warnings.is_empty());
+    }
+
+    #[test]
+    fn bad_rows_are_reported_together() {
+        let text = format!(
+            "{},5\n{}\n{},0\nnot an address,1\n{},1\n",
+            address(1),
+            address(2),
+            address(3),
+            AccountId32::from([4; 32]).to_ss58check_with_version(0u16.into())
+        );
+        match parse_transfers(&text, &Units::planck(), 42, None) {
+            Err(Error::Rows(a)) => {
+                assert_eq!(a.iter().map(|b| b.0).collect::<Vec<_>>(), vec![2, 3, 4, 5])
+            }
+            _ => panic!("expected row errors"),
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let text = format!("{},{}1\n", address(1), u128::MAX);
+        assert!(matches!(
+            parse_transfers(&text, &Units::planck(), 42, None),
+            Err(Error::Rows(_))
+        ));
+        let text = format!("{},{}\n{},1\n", address(1), u128::MAX, address(2));
+        assert!(matches!(
+            parse_transfers(&text, &Units::planck(), 42, None),
+            Err(Error::TotalOverflow)
+        ));
+    }
+
+    #[test]
+    fn repeated_recipient_and_small_amount_are_warned() {
+        let text = format!("{},5\n{},20\n{},10\n", address(1), address(2), address(1));
+        let read = parse_transfers(&text, &Units::planck(), 42, Some(10)).unwrap();
+        assert_eq!(read.transfers.len(), 3);
+        assert_eq!(
+            read.warnings.iter().map(|a| a.0).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+    }
+}
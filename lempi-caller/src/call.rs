@@ -50,7 +50,20 @@ impl CallField {
         for i in 0..card.indent {
             self.surface.add_change(" | ");
         }
+        if card.missing {
+            self.surface
+                .add_change(Change::Attribute(AttributeChange::Foreground(
+                    AnsiColor::Red.into(),
+                )));
+            self.surface.add_change("! ");
+        }
         self.surface.add_change(card.content);
+        if card.missing {
+            self.surface
+                .add_change(Change::Attribute(AttributeChange::Foreground(
+                    AnsiColor::White.into(),
+                )));
+        }
         self.surface.add_change("\r\n");
         if selected {
             self.surface
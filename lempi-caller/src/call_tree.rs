@@ -0,0 +1,317 @@
+use frame_metadata::v15::RuntimeMetadataV15;
+
+use primitive_types::H256;
+
+use substrate_constructor::fill_prepare::{
+    EraToFill, FieldToFill, PrimitiveToFill, RegularPrimitiveToFill, SpecialTypeToFill,
+    TypeContentToFill, UnsignedToFill, VariantSelector,
+};
+use substrate_constructor::finalize::Finalize;
+use substrate_constructor::try_fill::TryFill;
+
+use substrate_parser::additional_types::AccountId32;
+
+/// Errors of programmatic call construction
+#[derive(Debug)]
+pub enum Error {
+    NoField(String),
+    NoVariant(String),
+    NotFilled(String),
+    UnexpectedType(String),
+    VariantConstruction(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NoField(a) => write!(f, "no field {}", a),
+            Error::NoVariant(a) => write!(f, "no variant {}", a),
+            Error::NotFilled(a) => write!(f, "{} does not fit, field is unchanged", a),
+            Error::UnexpectedType(a) => write!(f, "unexpected type, expected {}", a),
+            Error::VariantConstruction(a) => write!(f, "could not make variant {}", a),
+        }
+    }
+}
+
+/// Select variant by name and give its fields, e.g. pallet in `RuntimeCall` or call in pallet
+pub fn select_variant<'a>(
+    content: &'a mut TypeContentToFill,
+    name: &str,
+    metadata: &RuntimeMetadataV15,
+) -> Result<&'a mut Vec<FieldToFill>, Error> {
+    match content {
+        TypeContentToFill::Variant(ref mut a) => {
+            if a.selected.name != name {
+                let index = a
+                    .available_variants
+                    .iter()
+                    .position(|v| v.name == name)
+                    .ok_or(Error::NoVariant(name.to_string()))?;
+                *a = VariantSelector::new_at::<(), RuntimeMetadataV15>(
+                    &a.available_variants,
+                    &mut (),
+                    &metadata.types,
+                    index,
+                )
+                .map_err(|_| Error::VariantConstruction(name.to_string()))?;
+            }
+            Ok(&mut a.selected.fields_to_fill)
+        }
+        _ => Err(Error::UnexpectedType(format!("enum with variant {}", name))),
+    }
+}
+
+/// Single unnamed field, e.g. pallet calls inside of `RuntimeCall` variant
+pub fn only_field(fields: &mut [FieldToFill]) -> Result<&mut TypeContentToFill, Error> {
+    match fields {
+        [a] => Ok(&mut a.type_to_fill.content),
+        _ => Err(Error::NoField("single field".to_string())),
+    }
+}
+
+/// Field by name, from composite or selected variant fields
+pub fn field<'a>(
+    fields: &'a mut [FieldToFill],
+    name: &str,
+) -> Result<&'a mut TypeContentToFill, Error> {
+    fields
+        .iter_mut()
+        .find(|a| a.field_name.as_deref() == Some(name))
+        .map(|a| &mut a.type_to_fill.content)
+        .ok_or(Error::NoField(name.to_string()))
+}
+
+/// Fields of struct
+pub fn composite_fields(content: &mut TypeContentToFill) -> Result<&mut Vec<FieldToFill>, Error> {
+    match content {
+        TypeContentToFill::Composite(ref mut a) => Ok(a),
+        _ => Err(Error::UnexpectedType("struct".to_string())),
+    }
+}
+
+/// Set unsigned integer, compact or not; `Err` if value does not fit its type
+pub fn set_unsigned(content: &mut TypeContentToFill, value: u128) -> Result<(), Error> {
+    match content {
+        TypeContentToFill::Primitive(
+            PrimitiveToFill::CompactUnsigned(_) | PrimitiveToFill::Unsigned(_),
+        ) => set_from_str(content, &value.to_string()),
+        _ => Err(Error::UnexpectedType("unsigned integer".to_string())),
+    }
+}
+
+/// Set primitive or special type from text
+///
+/// Text is put into a copy without value, and the copy replaces field only if text alone
+/// filled it, so that text which does not parse gives `Err` instead of keeping old value.
+pub fn set_from_str(content: &mut TypeContentToFill, value: &str) -> Result<(), Error> {
+    let mut filled = content.clone();
+    match filled {
+        TypeContentToFill::Primitive(
+            PrimitiveToFill::CompactUnsigned(ref mut a) | PrimitiveToFill::Unsigned(ref mut a),
+        ) => {
+            clear_unsigned(&mut a.content);
+            a.content.upd_from_str(value);
+        }
+        TypeContentToFill::Primitive(PrimitiveToFill::Regular(ref mut a)) => {
+            clear_regular(a);
+            a.upd_from_str(value);
+        }
+        TypeContentToFill::SpecialType(ref mut a) => {
+            clear_special(a);
+            a.upd_from_str(value);
+        }
+        _ => return Err(Error::UnexpectedType("primitive".to_string())),
+    }
+    if filled.finalize().is_none() {
+        return Err(Error::NotFilled(value.to_string()));
+    }
+    *content = filled;
+    Ok(())
+}
+
+fn clear_unsigned(content: &mut UnsignedToFill) {
+    match content {
+        UnsignedToFill::U8(a) => *a = None,
+        UnsignedToFill::U16(a) => *a = None,
+        UnsignedToFill::U32(a) => *a = None,
+        UnsignedToFill::U64(a) => *a = None,
+        UnsignedToFill::U128(a) => *a = None,
+    }
+}
+
+fn clear_regular(content: &mut RegularPrimitiveToFill) {
+    match content {
+        RegularPrimitiveToFill::Bool(a) => *a = None,
+        RegularPrimitiveToFill::Char(a) => *a = None,
+        RegularPrimitiveToFill::I8(a) => *a = None,
+        RegularPrimitiveToFill::I16(a) => *a = None,
+        RegularPrimitiveToFill::I32(a) => *a = None,
+        RegularPrimitiveToFill::I64(a) => *a = None,
+        RegularPrimitiveToFill::I128(a) => *a = None,
+        RegularPrimitiveToFill::I256(a) => *a = None,
+        RegularPrimitiveToFill::Str(a) => a.clear(),
+        RegularPrimitiveToFill::U256(a) => *a = None,
+    }
+}
+
+fn clear_special(content: &mut SpecialTypeToFill) {
+    match content {
+        SpecialTypeToFill::AccountId32(a) => *a = None,
+        // text fills only mortal era, as period and phase
+        SpecialTypeToFill::Era(a) => {
+            *a = EraToFill::Mortal {
+                period: None,
+                phase: None,
+            }
+        }
+        SpecialTypeToFill::H256 { hash, .. } => *hash = None,
+        SpecialTypeToFill::PerU16 { value, .. } => *value = None,
+        SpecialTypeToFill::Perbill { value, .. } => *value = None,
+        SpecialTypeToFill::Percent { value, .. } => *value = None,
+        SpecialTypeToFill::Permill { value, .. } => *value = None,
+        SpecialTypeToFill::Perquintill { value, .. } => *value = None,
+        SpecialTypeToFill::PublicEd25519(a) => *a = None,
+        SpecialTypeToFill::PublicSr25519(a) => *a = None,
+        SpecialTypeToFill::PublicEcdsa(a) => *a = None,
+        SpecialTypeToFill::SignatureEd25519(a) => *a = None,
+        SpecialTypeToFill::SignatureSr25519(a) => *a = None,
+        SpecialTypeToFill::SignatureEcdsa(a) => *a = None,
+    }
+}
+
+/// Set account, either plain `AccountId32` or `Id` variant of `MultiAddress`
+pub fn set_account_id(
+    content: &mut TypeContentToFill,
+    account_id: H256,
+    metadata: &RuntimeMetadataV15,
+) -> Result<(), Error> {
+    match content {
+        TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(ref mut a)) => {
+            *a = Some(AccountId32(account_id.0));
+            Ok(())
+        }
+        TypeContentToFill::Variant(_) => {
+            let fields = select_variant(content, "Id", metadata)?;
+            set_account_id(only_field(fields)?, account_id, metadata)
+        }
+        _ => Err(Error::UnexpectedType("account".to_string())),
+    }
+}
+
+/// Set number of elements of sequence and give elements to fill
+pub fn set_sequence_length<'a>(
+    content: &'a mut TypeContentToFill,
+    length: usize,
+    metadata: &RuntimeMetadataV15,
+) -> Result<&'a mut Vec<TypeContentToFill>, Error> {
+    match content {
+        TypeContentToFill::SequenceRegular(ref mut a) => {
+            a.set_number_of_elements::<(), RuntimeMetadataV15>(&mut (), &metadata.types, length)
+                .map_err(|_| Error::UnexpectedType("sequence".to_string()))?;
+            Ok(&mut a.content)
+        }
+        _ => Err(Error::UnexpectedType("sequence".to_string())),
+    }
+}
+
+/// Set sequence of accounts
+pub fn set_account_ids(
+    content: &mut TypeContentToFill,
+    account_ids: &[H256],
+    metadata: &RuntimeMetadataV15,
+) -> Result<(), Error> {
+    let elements = set_sequence_length(content, account_ids.len(), metadata)?;
+    for (element, account_id) in elements.iter_mut().zip(account_ids) {
+        set_account_id(element, *account_id, metadata)?;
+    }
+    Ok(())
+}
+
+/// Set byte array or byte sequence
+pub fn set_bytes(content: &mut TypeContentToFill, bytes: &[u8]) -> Result<(), Error> {
+    match content {
+        TypeContentToFill::ArrayU8(ref mut a) if a.len as usize == bytes.len() => {
+            a.content = bytes.to_vec();
+            Ok(())
+        }
+        TypeContentToFill::SequenceU8(ref mut a) => {
+            a.content = bytes.to_vec();
+            Ok(())
+        }
+        _ => Err(Error::UnexpectedType(format!("{} bytes", bytes.len()))),
+    }
+}
+
+/// Set `Option` to `None`, or to `Some` and give fields to fill
+pub fn set_option<'a>(
+    content: &'a mut TypeContentToFill,
+    some: bool,
+    metadata: &RuntimeMetadataV15,
+) -> Result<&'a mut Vec<FieldToFill>, Error> {
+    select_variant(content, if some { "Some" } else { "None" }, metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use substrate_constructor::fill_prepare::SpecialtyUnsignedToFill;
+    use substrate_parser::special_indicators::SpecialtyUnsignedInteger;
+
+    fn u8_field(value: Option<u8>) -> TypeContentToFill {
+        TypeContentToFill::Primitive(PrimitiveToFill::Unsigned(SpecialtyUnsignedToFill {
+            content: UnsignedToFill::U8(value),
+            specialty: SpecialtyUnsignedInteger::None,
+        }))
+    }
+
+    fn u8_value(content: &TypeContentToFill) -> Option<u8> {
+        match content {
+            TypeContentToFill::Primitive(PrimitiveToFill::Unsigned(SpecialtyUnsignedToFill {
+                content: UnsignedToFill::U8(a),
+                ..
+            })) => *a,
+            _ => panic!("not u8"),
+        }
+    }
+
+    #[test]
+    fn unsigned_overflow_keeps_value() {
+        let mut content = u8_field(Some(7));
+        set_unsigned(&mut content, 255).unwrap();
+        assert_eq!(u8_value(&content), Some(255));
+        assert!(matches!(
+            set_unsigned(&mut content, 256),
+            Err(Error::NotFilled(_))
+        ));
+        assert_eq!(u8_value(&content), Some(255));
+    }
+
+    #[test]
+    fn text_that_does_not_parse_keeps_value() {
+        let mut content = TypeContentToFill::Primitive(PrimitiveToFill::Regular(
+            RegularPrimitiveToFill::I8(Some(-1)),
+        ));
+        assert!(set_from_str(&mut content, "-128").is_ok());
+        for text in ["128", "x", ""] {
+            assert!(set_from_str(&mut content, text).is_err(), "{}", text);
+        }
+        assert!(matches!(
+            content,
+            TypeContentToFill::Primitive(PrimitiveToFill::Regular(RegularPrimitiveToFill::I8(
+                Some(-128)
+            )))
+        ));
+    }
+
+    #[test]
+    fn perthing_from_parts() {
+        let mut content = TypeContentToFill::SpecialType(SpecialTypeToFill::Percent {
+            value: None,
+            is_compact: false,
+        });
+        assert!(set_from_str(&mut content, "percent").is_err());
+        set_from_str(&mut content, "50").unwrap();
+        assert!(content.finalize().is_some());
+    }
+}
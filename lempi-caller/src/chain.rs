@@ -1,11 +1,11 @@
 use frame_metadata::{v15::RuntimeMetadataV15, RuntimeMetadata};
 
-use parity_scale_codec::Decode;
+use parity_scale_codec::{Compact, Decode, Encode};
 
 use primitive_types::H256;
 
 use serde::Deserialize;
-use serde_json::{value::Value, Map, Number};
+use serde_json::{json, value::Value, Map, Number};
 
 use smoldot_light::{
     platform::DefaultPlatform, AddChainConfig, AddChainSuccess, ChainId, Client, JsonRpcResponses,
@@ -25,13 +25,26 @@ use tokio::{
     time::{sleep, Duration},
 };
 
-use crate::author::Address;
-
 struct NonceRequest {
-    id: H256,
+    /// Account as RPC expects it: ss58 or hex
+    id: String,
     res: Option<u64>,
 }
 
+struct StorageRequest {
+    /// Storage key in hex
+    key: String,
+    /// `None` while waiting, `Ok(None)` if there is no value, `Err` if node refused request
+    res: Option<Result<Option<Vec<u8>>, String>>,
+}
+
+struct WeightRequest {
+    /// Call data in hex
+    call: String,
+    /// `ref_time` and `proof_size`, or why runtime could not estimate them
+    res: Option<Result<(u64, u64), String>>,
+}
+
 /// Abstraction to connect to chain
 ///
 /// This should run asynchronously under the hood and provide easy synchronous observables
@@ -43,6 +56,10 @@ pub struct Blockchain {
     res: mpsc::Receiver<Value>,
     metadata: RuntimeMetadataV15,
     nonce_request: Option<NonceRequest>,
+    storage_request: Option<StorageRequest>,
+    weight_request: Option<WeightRequest>,
+    /// Latest status of submitted extrinsic, or error if node refused it
+    submission: Option<Value>,
     specs: Map<String, Value>,
     log: Vec<String>,
 }
@@ -54,7 +71,7 @@ impl Blockchain {
             env!("CARGO_PKG_VERSION").into(),
         ));
 
-        println!("{}", specpath);
+        eprintln!("{}", specpath);
         let mut spec = String::new();
         match File::open(specpath) {
             Ok(mut file) => file.read_to_string(&mut spec).unwrap(),
@@ -71,12 +88,12 @@ impl Blockchain {
                 max_subscriptions: u32::max_value(),
             },
         };
-        println!("smoldot started...");
+        eprintln!("smoldot started...");
         let AddChainSuccess {
             chain_id: id,
             json_rpc_responses: responses,
         } = client.add_chain(chain_config).unwrap();
-        println!("chain connected...");
+        eprintln!("chain connected...");
         let mut responses = responses.unwrap();
 
         client
@@ -97,7 +114,7 @@ impl Blockchain {
             panic!();
         };
 
-        println!("{} version {}", name, version);
+        eprintln!("{} version {}", name, version);
 
         let metadata_cache = metadata_cache(name, &version.to_string());
 
@@ -150,7 +167,7 @@ impl Blockchain {
             }
         };
 
-        println!("metadata fetched...");
+        eprintln!("metadata fetched...");
 
         let req = json_request(1, "chain_getBlockHash", r#"0"#);
         client.json_rpc_request(req, id).unwrap();
@@ -163,7 +180,7 @@ impl Blockchain {
         } else {
             panic!("block fetch failed")
         };
-        println!("genesis hash fetched...");
+        eprintln!("genesis hash fetched...");
 
         client
             .json_rpc_request(json_request(1, "chain_getBlockHash", ""), id)
@@ -177,7 +194,7 @@ impl Blockchain {
         } else {
             panic!("block fetch failed")
         };
-        println!("a block fetched...");
+        eprintln!("a block fetched...");
 
         let req = json_request(1, "system_properties", ""); //&format!("\"0x{}\"", hex::encode(block_hash.0)));
         client.json_rpc_request(req, id).unwrap();
@@ -188,7 +205,7 @@ impl Blockchain {
             Value::Object(a) => a.clone(),
             _ => panic!("specs is not a map: {:?}", specs),
         };
-        println!("specs fetched...");
+        eprintln!("specs fetched...");
 
         // Start block reception
         client
@@ -214,6 +231,9 @@ impl Blockchain {
             res,
             metadata,
             nonce_request: None,
+            storage_request: None,
+            weight_request: None,
+            submission: None,
             specs,
             log: Vec::new(),
         }
@@ -235,50 +255,71 @@ impl Blockchain {
         self.specs.clone()
     }
 
-    pub fn nonce(&mut self, address: H256, ss58: u16) -> Option<u64> {
+    pub fn nonce(&mut self, account: String) -> Option<u64> {
         match &self.nonce_request {
             Some(a) => {
-                if a.id == address {
+                if a.id == account {
                     a.res
                 } else {
+                    let req =
+                        json_request(2, "system_accountNextIndex", &format!("\"{}\"", account));
                     self.nonce_request = Some(NonceRequest {
-                        id: address,
+                        id: account,
                         res: None,
                     });
-                    let req = json_request(
-                        2,
-                        "system_accountNextIndex",
-                        &format!(
-                            "\"{}\"",
-                            Address::from_public(address)
-                                .into_account_id32()
-                                .as_base58(ss58)
-                                .to_string()
-                        ),
-                    );
                     self.client.json_rpc_request(req, self.id).unwrap();
 
                     None
                 }
             }
             None => {
+                let req = json_request(2, "system_accountNextIndex", &format!("\"{}\"", account));
                 self.nonce_request = Some(NonceRequest {
-                    id: address,
+                    id: account,
                     res: None,
                 });
+                self.client.json_rpc_request(req, self.id).unwrap();
+
+                None
+            }
+        }
+    }
+
+    /// Storage value at key, at best block; re-requested with each new finalized block
+    pub fn storage(&mut self, key: &[u8]) -> Option<Result<Option<Vec<u8>>, String>> {
+        let key = format!("0x{}", hex::encode(key));
+        match &self.storage_request {
+            Some(a) if a.key == key => a.res.clone(),
+            _ => {
+                let req = json_request(3, "state_getStorage", &format!("\"{}\"", key));
+                self.storage_request = Some(StorageRequest { key, res: None });
+                self.client.json_rpc_request(req, self.id).unwrap();
+                None
+            }
+        }
+    }
+
+    /// Weight of call, as estimated by runtime
+    pub fn call_weight(&mut self, call: &[u8]) -> Option<Result<(u64, u64), String>> {
+        let call_hex = hex::encode(call);
+        match &self.weight_request {
+            Some(a) if a.call == call_hex => a.res.clone(),
+            _ => {
+                let mut params = call.to_vec();
+                (call.len() as u32).encode_to(&mut params);
                 let req = json_request(
-                    2,
-                    "system_accountNextIndex",
+                    4,
+                    "state_call",
                     &format!(
-                        "\"{}\"",
-                        Address::from_public(address)
-                            .into_account_id32()
-                            .as_base58(ss58)
-                            .to_string()
+                        "\"TransactionPaymentCallApi_query_call_info\", \"0x{}\"",
+                        hex::encode(params)
                     ),
                 );
+                self.weight_request = Some(WeightRequest {
+                    call: call_hex,
+                    res: None,
+                });
                 self.client.json_rpc_request(req, self.id).unwrap();
-
                 None
             }
         }
@@ -291,9 +332,35 @@ impl Blockchain {
             &format!("\"0x{}\"", hex::encode(&unchecked_extrinsic)),
         );
         self.log.push(format!("{}", req));
+        self.submission = None;
         self.client.json_rpc_request(req, self.id).unwrap();
     }
 
+    /// Latest status of submitted extrinsic
+    pub fn submission(&self) -> Option<&Value> {
+        self.submission.as_ref()
+    }
+
+    /// Submitted extrinsic is finalized, or will not be
+    pub fn submission_finished(&self) -> bool {
+        match &self.submission {
+            Some(Value::String(a)) => matches!(a.as_str(), "dropped" | "invalid" | "usurped"),
+            Some(Value::Object(a)) => ["finalized", "finalityTimeout", "usurped", "error"]
+                .iter()
+                .any(|b| a.contains_key(*b)),
+            _ => false,
+        }
+    }
+
+    /// Submitted extrinsic will not be finalized
+    pub fn submission_failed(&self) -> bool {
+        let finalized = matches!(
+            &self.submission,
+            Some(Value::Object(a)) if a.contains_key("finalized")
+        );
+        self.submission_finished() && !finalized
+    }
+
     pub fn log(&mut self) -> String {
         let mut out = String::new();
         while let Some(a) = self.log.pop() {
@@ -311,9 +378,16 @@ impl Blockchain {
             let mut unknown = true;
             match &a["method"] {
                 Value::String(s) => match s.as_str() {
+                    "author_extrinsicUpdate" => {
+                        let status = a["params"]["result"].clone();
+                        self.log.push(format!("extrinsic status: {}", status));
+                        self.submission = Some(status);
+                        unknown = false;
+                    }
                     "chain_finalizedHead" => match &a["params"]["result"]["parentHash"] {
                         Value::String(h) => {
                             self.block_hash = H256(unhex(&h).unwrap().try_into().unwrap());
+                            self.storage_request = None;
                             unknown = false;
                         }
                         _ => (),
@@ -333,7 +407,57 @@ impl Blockchain {
                     },
                     _ => (),
                 },
-                Some(9) => self.log.push(format!("submitted: {:?}", a)),
+                Some(3) => match (&mut self.storage_request, a.get("result")) {
+                    (Some(ref mut b), Some(Value::String(h))) => {
+                        b.res = Some(
+                            unhex(h)
+                                .map(Some)
+                                .map_err(|e| format!("storage value: {:?}", e)),
+                        );
+                        unknown = false;
+                    }
+                    (Some(ref mut b), Some(Value::Null)) => {
+                        b.res = Some(Ok(None));
+                        unknown = false;
+                    }
+                    (Some(ref mut b), None) => {
+                        let e = format!("storage request failed: {}", a["error"]);
+                        self.log.push(e.to_owned());
+                        b.res = Some(Err(e));
+                        unknown = false;
+                    }
+                    _ => (),
+                },
+                Some(4) => match (&mut self.weight_request, &a["result"]) {
+                    (Some(ref mut b), Value::String(h)) => {
+                        // `RuntimeDispatchInfo` starts with weight
+                        b.res = Some(
+                            unhex(h)
+                                .ok()
+                                .and_then(|info| {
+                                    <(Compact<u64>, Compact<u64>)>::decode(&mut &info[..]).ok()
+                                })
+                                .map(|(Compact(ref_time), Compact(proof_size))| {
+                                    (ref_time, proof_size)
+                                })
+                                .ok_or("could not read call weight".to_string()),
+                        );
+                        unknown = false;
+                    }
+                    (Some(ref mut b), Value::Null) => {
+                        let e = format!("call weight request failed: {}", a["error"]);
+                        self.log.push(e.to_owned());
+                        b.res = Some(Err(e));
+                        unknown = false;
+                    }
+                    _ => (),
+                },
+                Some(9) => {
+                    if let Some(e) = a.get("error") {
+                        self.submission = Some(json!({ "error": e }));
+                    }
+                    self.log.push(format!("submitted: {:?}", a))
+                }
                 _ => (),
             }
             if unknown {
@@ -0,0 +1,443 @@
+use bip39::{Language, Mnemonic};
+
+use blake2::{Blake2b512, Digest};
+
+use parity_scale_codec::Encode;
+
+use rand::{rngs::OsRng, RngCore};
+
+use sp_core::blake2_256;
+
+/// Development phrase, root of `//Alice`, `//Bob` and the rest of dev accounts
+pub const DEV_PHRASE: &str =
+    "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+
+/// Public key of `DEV_PHRASE` in sr25519, in default SS58 format
+pub const DEV_ADDRESS: &str = "5DfhGyQdFobKM8NsWvEeAKk5EQQgYe9AydgJ7rMB6E1EqRzV";
+
+/// Chain code length, as in schnorrkel
+pub const JUNCTION_ID_LEN: usize = 32;
+
+/// Default SS58 format, generic substrate
+const DEFAULT_SS58_FORMAT: u16 = 42;
+
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+const CHECKSUM_LEN: usize = 2;
+
+/// Errors of secret phrase and derivation string parsing
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SecretStringError {
+    InvalidFormat,
+    InvalidPath,
+    InvalidPhrase,
+    InvalidSeed,
+    InvalidSeedLength,
+}
+
+impl std::fmt::Display for SecretStringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SecretStringError::InvalidFormat => write!(f, "invalid format"),
+            SecretStringError::InvalidPath => write!(f, "invalid derivation path"),
+            SecretStringError::InvalidPhrase => write!(f, "invalid phrase"),
+            SecretStringError::InvalidSeed => write!(f, "invalid seed"),
+            SecretStringError::InvalidSeedLength => write!(f, "invalid seed length"),
+        }
+    }
+}
+
+/// Errors of key derivation
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DeriveError {
+    SoftKeyInPath,
+}
+
+impl std::fmt::Display for DeriveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DeriveError::SoftKeyInPath => write!(f, "soft key in path"),
+        }
+    }
+}
+
+/// Errors of public key parsing
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PublicError {
+    BadBase58,
+    BadLength,
+    InvalidChecksum,
+    InvalidFormat,
+    InvalidPath,
+    InvalidPrefix,
+}
+
+impl std::fmt::Display for PublicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PublicError::BadBase58 => write!(f, "invalid base58"),
+            PublicError::BadLength => write!(f, "invalid length"),
+            PublicError::InvalidChecksum => write!(f, "invalid checksum"),
+            PublicError::InvalidFormat => write!(f, "invalid format"),
+            PublicError::InvalidPath => write!(f, "invalid derivation path"),
+            PublicError::InvalidPrefix => write!(f, "invalid SS58 prefix"),
+        }
+    }
+}
+
+/// Single step of derivation path, chain code for schnorrkel
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DeriveJunction {
+    Soft([u8; JUNCTION_ID_LEN]),
+    Hard([u8; JUNCTION_ID_LEN]),
+}
+
+impl DeriveJunction {
+    /// Soft junction from encoded index; encodings too long for chain code are hashed
+    pub fn soft<T: Encode>(index: T) -> Self {
+        let mut cc = [0u8; JUNCTION_ID_LEN];
+        index.using_encoded(|data| {
+            if data.len() > JUNCTION_ID_LEN {
+                cc.copy_from_slice(&blake2_256(data));
+            } else {
+                cc[..data.len()].copy_from_slice(data);
+            }
+        });
+        DeriveJunction::Soft(cc)
+    }
+
+    /// Hard junction from encoded index
+    pub fn hard<T: Encode>(index: T) -> Self {
+        Self::soft(index).harden()
+    }
+
+    pub fn harden(self) -> Self {
+        match self {
+            DeriveJunction::Soft(cc) | DeriveJunction::Hard(cc) => DeriveJunction::Hard(cc),
+        }
+    }
+}
+
+impl<T: AsRef<str>> From<T> for DeriveJunction {
+    /// Junction as written after `/`: leading `/` makes it hard, numbers are encoded as `u64`
+    fn from(j: T) -> DeriveJunction {
+        let j = j.as_ref();
+        let (code, hard) = match j.strip_prefix('/') {
+            Some(a) => (a, true),
+            None => (j, false),
+        };
+        let junction = match code.parse::<u64>() {
+            Ok(n) => DeriveJunction::soft(n),
+            Err(_) => DeriveJunction::soft(code),
+        };
+        if hard {
+            junction.harden()
+        } else {
+            junction
+        }
+    }
+}
+
+/// Parts of derivation string `<phrase or 0x seed>//hard/soft///password`
+///
+/// Root part could be empty, then it is `DEV_PHRASE` for secrets and `DEV_ADDRESS` for publics.
+struct Uri<'a> {
+    root: Option<&'a str>,
+    junctions: Vec<DeriveJunction>,
+    password: Option<&'a str>,
+}
+
+impl<'a> Uri<'a> {
+    fn parse(input: &'a str) -> Option<Self> {
+        let (rest, password) = match input.find("///") {
+            Some(a) => (&input[..a], Some(&input[a + 3..])),
+            None => (input, None),
+        };
+        let (root, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        if root
+            .chars()
+            .any(|c| !(c.is_alphanumeric() || c == '_' || c == ' '))
+        {
+            return None;
+        }
+        Some(Self {
+            root: if root.is_empty() { None } else { Some(root) },
+            junctions: junctions(path)?,
+            password,
+        })
+    }
+}
+
+/// Derivation path alone, e.g. `//hard/soft`; empty path has no junctions
+pub fn junctions(path: &str) -> Option<Vec<DeriveJunction>> {
+    if !(path.is_empty() || path.starts_with('/')) {
+        return None;
+    }
+    let mut junctions = Vec::new();
+    let mut hard = false;
+    for part in path.split('/').skip(1) {
+        if part.is_empty() {
+            if hard {
+                return None;
+            }
+            hard = true;
+        } else {
+            let junction = DeriveJunction::from(part);
+            junctions.push(if hard { junction.harden() } else { junction });
+            hard = false;
+        }
+    }
+    if hard {
+        None
+    } else {
+        Some(junctions)
+    }
+}
+
+/// Four bytes to tell crypto apart
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CryptoTypeId(pub [u8; 4]);
+
+/// Type belonging to some crypto, with its key pair
+pub trait CryptoType {
+    type Pair: Pair;
+}
+
+/// Construction without any checks; only for data known to be valid
+pub trait UncheckedFrom<T> {
+    fn unchecked_from(t: T) -> Self;
+}
+
+/// Type made from entropy, e.g. public key filled from random bytes
+pub trait FromEntropy: Sized {
+    fn from_entropy(
+        input: &mut impl parity_scale_codec::Input,
+    ) -> Result<Self, parity_scale_codec::Error>;
+}
+
+/// Fixed-length byte array
+pub trait ByteArray: AsRef<[u8]> + AsMut<[u8]> + for<'a> TryFrom<&'a [u8], Error = ()> {
+    const LEN: usize;
+
+    fn from_slice(data: &[u8]) -> Result<Self, ()> {
+        Self::try_from(data)
+    }
+
+    fn to_raw_vec(&self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+/// Public key that could be derived further with soft junctions
+pub trait Derive: Sized {
+    /// `None` if there are hard junctions in path or key does not support derivation
+    fn derive<Iter: Iterator<Item = DeriveJunction>>(&self, _path: Iter) -> Option<Self> {
+        None
+    }
+}
+
+/// Public key
+pub trait Public: ByteArray + Derive + CryptoType + PartialEq + Eq + Clone + Send + Sync {}
+
+/// SS58 encoding of public keys
+pub trait Ss58Codec: Sized + AsMut<[u8]> + AsRef<[u8]> + ByteArray + Derive {
+    fn from_ss58check(s: &str) -> Result<Self, PublicError> {
+        Self::from_ss58check_with_version(s).map(|(a, _)| a)
+    }
+
+    /// Public key and SS58 format
+    fn from_ss58check_with_version(s: &str) -> Result<(Self, u16), PublicError> {
+        let data = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| PublicError::BadBase58)?;
+        if data.len() < 2 {
+            return Err(PublicError::BadLength);
+        }
+        let (prefix_len, format) = match data[0] {
+            0..=63 => (1, data[0] as u16),
+            64..=127 => {
+                let lower = (data[0] << 2) | (data[1] >> 6);
+                let upper = data[1] & 0b0011_1111;
+                (2, (lower as u16) | ((upper as u16) << 8))
+            }
+            _ => return Err(PublicError::InvalidPrefix),
+        };
+        if data.len() != prefix_len + Self::LEN + CHECKSUM_LEN {
+            return Err(PublicError::BadLength);
+        }
+        let body_end = prefix_len + Self::LEN;
+        if data[body_end..] != ss58hash(&data[..body_end])[..CHECKSUM_LEN] {
+            return Err(PublicError::InvalidChecksum);
+        }
+        let public =
+            Self::from_slice(&data[prefix_len..body_end]).map_err(|_| PublicError::BadLength)?;
+        Ok((public, format))
+    }
+
+    fn to_ss58check(&self) -> String {
+        self.to_ss58check_with_version(DEFAULT_SS58_FORMAT)
+    }
+
+    fn to_ss58check_with_version(&self, format: u16) -> String {
+        let format = format & 0b0011_1111_1111_1111;
+        let mut data = match format {
+            0..=63 => vec![format as u8],
+            _ => {
+                let first = ((format & 0b0000_0000_1111_1100) as u8) >> 2;
+                let second = ((format >> 8) as u8) | ((format & 0b0000_0000_0000_0011) as u8) << 6;
+                vec![first | 0b0100_0000, second]
+            }
+        };
+        data.extend(self.as_ref());
+        let hash = ss58hash(&data);
+        data.extend(&hash[..CHECKSUM_LEN]);
+        bs58::encode(data).into_string()
+    }
+
+    /// SS58 address or `0x` public key, with optional soft derivation path
+    fn from_string(s: &str) -> Result<Self, PublicError> {
+        let uri = Uri::parse(s).ok_or(PublicError::InvalidFormat)?;
+        if uri.password.is_some() {
+            return Err(PublicError::InvalidFormat);
+        }
+        let root = uri.root.unwrap_or(DEV_ADDRESS);
+        let public = match root.strip_prefix("0x") {
+            Some(a) => {
+                let bytes = hex::decode(a).map_err(|_| PublicError::InvalidFormat)?;
+                Self::from_slice(&bytes).map_err(|_| PublicError::BadLength)?
+            }
+            None => Self::from_ss58check(root)?,
+        };
+        if uri.junctions.is_empty() {
+            Ok(public)
+        } else {
+            public
+                .derive(uri.junctions.into_iter())
+                .ok_or(PublicError::InvalidPath)
+        }
+    }
+}
+
+impl<T: Public> Ss58Codec for T {}
+
+fn ss58hash(data: &[u8]) -> [u8; 64] {
+    Blake2b512::new()
+        .chain_update(SS58_PREFIX)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+/// Key pair
+pub trait Pair: CryptoType + Sized + Clone + Send + Sync + 'static {
+    type Public: Public + Ss58Codec;
+    type Seed: Default + AsRef<[u8]> + AsMut<[u8]> + Clone;
+    type Signature: AsRef<[u8]>;
+
+    fn public(&self) -> Self::Public;
+
+    /// Key pair from seed of `Self::Seed` length or other length supported by crypto
+    fn from_seed_slice(seed: &[u8]) -> Result<Self, SecretStringError>;
+
+    /// Derive key pair along path; seed is kept only if path is all hard
+    fn derive<Iter: Iterator<Item = DeriveJunction>>(
+        &self,
+        path: Iter,
+        seed: Option<Self::Seed>,
+    ) -> Result<(Self, Option<Self::Seed>), DeriveError>;
+
+    fn sign(&self, message: &[u8]) -> Self::Signature;
+
+    fn verify<M: AsRef<[u8]>>(sig: &Self::Signature, message: M, pubkey: &Self::Public) -> bool;
+
+    /// Raw secret
+    fn to_raw_vec(&self) -> Vec<u8>;
+
+    fn from_seed(seed: &Self::Seed) -> Self {
+        Self::from_seed_slice(seed.as_ref()).expect("seed has valid length; qed")
+    }
+
+    /// Random key pair with its seed
+    fn generate() -> (Self, Self::Seed) {
+        let mut seed = Self::Seed::default();
+        OsRng.fill_bytes(seed.as_mut());
+        (Self::from_seed(&seed), seed)
+    }
+
+    /// Random key pair with 12-word phrase and seed
+    fn generate_with_phrase(password: Option<&str>) -> (Self, String, Self::Seed) {
+        let mnemonic =
+            Mnemonic::generate_in(Language::English, 12).expect("12 words is valid length; qed");
+        let phrase = mnemonic.to_string();
+        let (pair, seed) =
+            Self::from_phrase(&phrase, password).expect("generated phrase is valid; qed");
+        (pair, phrase, seed)
+    }
+
+    /// Key pair from BIP39 phrase, with seed as in substrate: start of PBKDF2 over entropy
+    fn from_phrase(
+        phrase: &str,
+        password: Option<&str>,
+    ) -> Result<(Self, Self::Seed), SecretStringError> {
+        let mnemonic = Mnemonic::parse_in(Language::English, phrase)
+            .map_err(|_| SecretStringError::InvalidPhrase)?;
+        let big_seed =
+            substrate_bip39::seed_from_entropy(&mnemonic.to_entropy(), password.unwrap_or(""))
+                .map_err(|_| SecretStringError::InvalidSeed)?;
+        let mut seed = Self::Seed::default();
+        let seed_len = seed.as_ref().len();
+        seed.as_mut().copy_from_slice(&big_seed[..seed_len]);
+        Self::from_seed_slice(seed.as_ref()).map(|a| (a, seed))
+    }
+
+    /// Key pair from derivation string, `password_override` replaces password of string
+    fn from_string_with_seed(
+        s: &str,
+        password_override: Option<&str>,
+    ) -> Result<(Self, Option<Self::Seed>), SecretStringError> {
+        let uri = Uri::parse(s).ok_or(SecretStringError::InvalidFormat)?;
+        let password = password_override.or(uri.password);
+        let root = uri.root.unwrap_or(DEV_PHRASE);
+        let (pair, seed) = match root.strip_prefix("0x") {
+            Some(a) => {
+                let bytes = hex::decode(a).map_err(|_| SecretStringError::InvalidSeed)?;
+                let mut seed = Self::Seed::default();
+                if seed.as_ref().len() != bytes.len() {
+                    return Err(SecretStringError::InvalidSeedLength);
+                }
+                seed.as_mut().copy_from_slice(&bytes);
+                (Self::from_seed(&seed), seed)
+            }
+            None => Self::from_phrase(root, password)?,
+        };
+        pair.derive(uri.junctions.into_iter(), Some(seed))
+            .map_err(|_| SecretStringError::InvalidPath)
+    }
+
+    fn from_string(s: &str, password_override: Option<&str>) -> Result<Self, SecretStringError> {
+        Self::from_string_with_seed(s, password_override).map(|(a, _)| a)
+    }
+}
+
+/// Crypto with verifiable random function
+pub trait VrfCrypto {
+    type VrfInput;
+    type VrfPreOutput;
+    type VrfSignData;
+    type VrfSignature;
+}
+
+/// VRF signing
+pub trait VrfSecret: VrfCrypto {
+    fn vrf_pre_output(&self, data: &Self::VrfInput) -> Self::VrfPreOutput;
+
+    fn vrf_sign(&self, input: &Self::VrfSignData) -> Self::VrfSignature;
+}
+
+/// VRF verification
+pub trait VrfPublic: VrfCrypto {
+    fn vrf_verify(&self, data: &Self::VrfSignData, signature: &Self::VrfSignature) -> bool;
+}
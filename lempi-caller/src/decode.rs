@@ -0,0 +1,315 @@
+use frame_metadata::v15::RuntimeMetadataV15;
+
+use parity_scale_codec::{Decode, Encode};
+
+use primitive_types::{H160, H256};
+
+use scale_info::TypeDef;
+
+use serde_json::{json, Value};
+
+use sp_core::blake2_256;
+
+use substrate_parser::{
+    cards::Call, decode_as_call_unmarked, decode_as_unchecked_extrinsic,
+    unchecked_extrinsic::UncheckedExtrinsic, AsMetadata, ShortSpecs,
+};
+
+use crate::author::{AccountId, Crypto};
+use crate::draft::Node;
+use crate::import::{self, decode, Decoded, Part, Signed};
+use crate::message::{verify_ethereum, verify_exact};
+use crate::units::Units;
+
+#[derive(Debug)]
+pub enum Error {
+    Import(import::Error),
+    InvalidBlock(String),
+    Parser(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Import(a) => write!(f, "{}", a),
+            Error::InvalidBlock(a) => write!(f, "invalid block hash: {}", a),
+            Error::Parser(a) => write!(f, "extrinsic does not match metadata: {}", a),
+        }
+    }
+}
+
+/// Runtime versions that go into signable payload
+struct Versions {
+    spec: u32,
+    transaction: u32,
+}
+
+/// Decode extrinsic or call hex and check its signature
+///
+/// Mortal extrinsic is signed over hash of its birth block, which can not be found from
+/// extrinsic itself; without `block` its signature is reported as not verifiable. Payload is
+/// rebuilt with runtime versions of current metadata, so extrinsic signed before runtime
+/// upgrade does not verify; versions used are reported along with signature check.
+pub fn report(
+    input: &str,
+    block: Option<&str>,
+    metadata: &RuntimeMetadataV15,
+    genesis_hash: H256,
+    units: &Units,
+    ss58: u16,
+) -> Result<Value, Error> {
+    let block = block
+        .map(|a| {
+            let bytes = hex::decode(a.trim().trim_start_matches("0x"))
+                .map_err(|e| Error::InvalidBlock(e.to_string()))?;
+            let bytes: [u8; 32] = bytes
+                .try_into()
+                .map_err(|a: Vec<u8>| Error::InvalidBlock(format!("{} bytes", a.len())))?;
+            Ok(H256(bytes))
+        })
+        .transpose()?;
+    let decoded = decode(input, metadata).map_err(Error::Import)?;
+    let call = parse(input, &decoded, metadata)?;
+    let short_specs = ShortSpecs {
+        base58prefix: ss58,
+        decimals: units.decimals(),
+        unit: units.symbol().to_owned(),
+    };
+    let spec_name = <RuntimeMetadataV15 as AsMetadata<()>>::spec_name_version(metadata)
+        .map(|a| a.spec_name)
+        .unwrap_or_default();
+
+    let mut out = json!({
+        "version": decoded.version,
+        "signed": decoded.signed.is_some(),
+        "call": decoded.call.node,
+        "call_text": call
+            .card(0, &short_specs, &spec_name)
+            .iter()
+            .map(|a| a.show())
+            .collect::<Vec<String>>(),
+    });
+    if let Some(ref signed) = decoded.signed {
+        let signer = first(&signed.address.node, &|a| match a {
+            Node::Account(Some(b)) => hex::decode(b)
+                .ok()?
+                .try_into()
+                .ok()
+                .map(|c| AccountId::Id32(H256(c))),
+            Node::Bytes(b) => hex::decode(b)
+                .ok()?
+                .try_into()
+                .ok()
+                .map(|c| AccountId::Id20(H160(c))),
+            _ => None,
+        });
+        out["signer"] = match signer {
+            Some(ref a) => json!(a.name(ss58)),
+            None => json!(signed.address.node),
+        };
+        out["extensions"] = extensions(signed);
+        out["signature"] = match versions(metadata) {
+            Some(versions) => {
+                let checked = signature(
+                    signed,
+                    signer,
+                    &decoded.call,
+                    metadata,
+                    &versions,
+                    genesis_hash,
+                    block,
+                );
+                let runtime = json!({
+                    "spec_version": versions.spec,
+                    "transaction_version": versions.transaction,
+                });
+                match checked {
+                    Ok(crypto) => json!({ "valid": true, "crypto": crypto, "runtime": runtime }),
+                    Err(reason) => json!({ "valid": false, "reason": reason, "runtime": runtime }),
+                }
+            }
+            None => json!({ "valid": false, "reason": "runtime version is not in metadata" }),
+        };
+    }
+    Ok(out)
+}
+
+/// Call as read by parser, which decodes whole extrinsic on its own
+fn parse(input: &str, decoded: &Decoded, metadata: &RuntimeMetadataV15) -> Result<Call, Error> {
+    if decoded.version.is_none() {
+        let mut position = 0;
+        let call = decode_as_call_unmarked(
+            &decoded.call.bytes.as_slice(),
+            &mut position,
+            &mut (),
+            metadata,
+        )
+        .map_err(|e| Error::Parser(format!("{:?}", e)))?;
+        return match decoded.call.bytes.len() - position {
+            0 => Ok(call),
+            a => Err(Error::Parser(format!("{} bytes left after call", a))),
+        };
+    }
+    let bytes = hex::decode(input.trim().trim_start_matches("0x"))
+        .map_err(|e| Error::Import(import::Error::InvalidHex(e.to_string())))?;
+    match decode_as_unchecked_extrinsic(&bytes.as_slice(), &mut (), metadata)
+        .map_err(|e| Error::Parser(format!("{:?}", e)))?
+    {
+        UncheckedExtrinsic::Signed { call, .. } | UncheckedExtrinsic::Unsigned { call } => Ok(call),
+    }
+}
+
+/// Extensions as decoded, with era, nonce, tip and metadata hash mode picked out
+fn extensions(signed: &Signed) -> Value {
+    let mut out = json!({});
+    for (identifier, part) in signed.extensions.iter() {
+        let unsigned = first(&part.node, &|a| match a {
+            Node::Unsigned(Some(b)) => Some(b.to_owned()),
+            _ => None,
+        });
+        match identifier.as_str() {
+            "CheckMortality" | "CheckEra" => out["era"] = era(&part.bytes),
+            "CheckNonce" => out["nonce"] = json!(unsigned),
+            "ChargeTransactionPayment" | "ChargeAssetTxPayment" => out["tip"] = json!(unsigned),
+            "CheckMetadataHash" => {
+                out["metadata_hash"] = match part.bytes.first() {
+                    Some(0) => json!("disabled"),
+                    _ => json!("enabled"),
+                }
+            }
+            _ => {}
+        }
+    }
+    out["all"] = signed
+        .extensions
+        .iter()
+        .map(|(identifier, part)| json!({ "identifier": identifier, "value": part.node }))
+        .collect();
+    out
+}
+
+/// Immortal, or period and phase of mortal era
+fn era(bytes: &[u8]) -> Value {
+    match bytes {
+        [first, second] => {
+            let encoded = u16::from_le_bytes([*first, *second]);
+            let period = 2u64 << (encoded % (1 << 4));
+            let quantize_factor = (period >> 12).max(1);
+            let phase = (encoded as u64 >> 4) * quantize_factor;
+            json!({ "period": period, "phase": phase })
+        }
+        _ => json!("immortal"),
+    }
+}
+
+/// Rebuild signable payload and check signature over it
+fn signature(
+    signed: &Signed,
+    signer: Option<AccountId>,
+    call: &Part,
+    metadata: &RuntimeMetadataV15,
+    versions: &Versions,
+    genesis_hash: H256,
+    block: Option<H256>,
+) -> Result<Crypto, String> {
+    let signer = signer.ok_or("signer is not a 32-byte or 20-byte account")?;
+    let signature = first(&signed.signature.node, &|a| match a {
+        Node::Bytes(b) => hex::decode(b).ok(),
+        _ => None,
+    })
+    .ok_or("signature is not in bytes")?;
+
+    let mut payload = call.bytes.to_owned();
+    for (_, part) in signed.extensions.iter() {
+        payload.extend_from_slice(&part.bytes);
+    }
+    for (extension, (identifier, part)) in metadata
+        .extrinsic
+        .signed_extensions
+        .iter()
+        .zip(signed.extensions.iter())
+    {
+        let additional = match identifier.as_str() {
+            "CheckSpecVersion" => versions.spec.encode(),
+            "CheckTxVersion" => versions.transaction.encode(),
+            "CheckGenesis" => genesis_hash.0.to_vec(),
+            "CheckMortality" | "CheckEra" => match (part.bytes.as_slice(), block) {
+                ([0], _) => genesis_hash.0.to_vec(),
+                (_, Some(a)) => a.0.to_vec(),
+                (_, None) => {
+                    return Err(
+                        "mortal era is signed over its birth block; give its hash to verify"
+                            .to_string(),
+                    )
+                }
+            },
+            // only mode without hash could be rebuilt: hash would need whole metadata digest
+            "CheckMetadataHash" => match part.bytes.as_slice() {
+                [0] => None::<[u8; 32]>.encode(),
+                _ => return Err("metadata hash is enabled and could not be checked".to_string()),
+            },
+            _ if is_empty(metadata, extension.additional_signed.id) => Vec::new(),
+            _ => return Err(format!("extension {} is not known", identifier)),
+        };
+        payload.extend_from_slice(&additional);
+    }
+    if payload.len() > 256 {
+        payload = blake2_256(&payload).to_vec();
+    }
+
+    let verified = match signer {
+        AccountId::Id32(a) => verify_exact(&a, &payload, &signature),
+        AccountId::Id20(a) => {
+            verify_ethereum(&a, &payload, &signature).map(|b| Some(Crypto::Ethereum).filter(|_| b))
+        }
+    };
+    match verified {
+        Ok(Some(crypto)) => Ok(crypto),
+        Ok(None) => Err(format!(
+            "signature does not match signer and payload with spec version {} and transaction version {} of current runtime; extrinsic signed before runtime upgrade does not verify",
+            versions.spec, versions.transaction
+        )),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Spec and transaction versions, from `System` constant `Version`
+fn versions(metadata: &RuntimeMetadataV15) -> Option<Versions> {
+    let constant = metadata
+        .pallets
+        .iter()
+        .find(|a| a.name == "System")?
+        .constants
+        .iter()
+        .find(|a| a.name == "Version")?;
+    // spec name, impl name, authoring version, spec version, impl version, apis, tx version
+    let (_, _, _, spec, _, _, transaction) =
+        <(String, String, u32, u32, u32, Vec<([u8; 8], u32)>, u32)>::decode(
+            &mut &constant.value[..],
+        )
+        .ok()?;
+    Some(Versions { spec, transaction })
+}
+
+/// Type encodes into nothing
+fn is_empty(metadata: &RuntimeMetadataV15, id: u32) -> bool {
+    match metadata.types.resolve(id).map(|a| &a.type_def) {
+        Some(TypeDef::Tuple(a)) => a.fields.iter().all(|b| is_empty(metadata, b.id)),
+        Some(TypeDef::Composite(a)) => a.fields.iter().all(|b| is_empty(metadata, b.ty.id)),
+        _ => false,
+    }
+}
+
+/// First node in tree, depth first, that gives value
+fn first<T>(node: &Node, f: &dyn Fn(&Node) -> Option<T>) -> Option<T> {
+    if let Some(a) = f(node) {
+        return Some(a);
+    }
+    match node {
+        Node::Array(a) | Node::Sequence(a) | Node::Tuple(a) => a.iter().find_map(|b| first(b, f)),
+        Node::Composite(a) | Node::Variant { fields: a, .. } => {
+            a.iter().find_map(|b| first(&b.node, f))
+        }
+        _ => None,
+    }
+}
@@ -31,7 +31,14 @@ impl Details {
         if let Some(selector) = card.selector {
             self.surface.add_change("\n\r");
             self.surface.add_change("\n\r");
-            for (index, item) in selector.list.iter().enumerate() {
+            if !selector.filter().is_empty() {
+                self.surface
+                    .add_change(format!("Filter: {}\n\r", selector.filter()));
+                if selector.is_empty() {
+                    self.surface.add_change("(nothing matches)\n\r");
+                }
+            }
+            for (index, item) in selector.visible() {
                 if cursor_seen {
                     midscreen += 1;
                     if midscreen > midscreen_threshold {
@@ -64,6 +71,11 @@ impl Details {
             self.surface.add_change(buffer.clone());
         }
 
+        if let Some(preview) = card.preview {
+            self.surface.add_change("\n\r");
+            self.surface.add_change(preview);
+        }
+
         &self.surface
     }
 }
@@ -0,0 +1,493 @@
+use frame_metadata::v15::RuntimeMetadataV15;
+
+use parity_scale_codec::Encode;
+
+use primitive_types::H256;
+
+use serde::{Deserialize, Serialize};
+
+use sp_core::blake2_256;
+
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::PathBuf,
+};
+
+use substrate_constructor::fill_prepare::{
+    EraToFill, FieldToFill, PrimitiveToFill, RegularPrimitiveToFill, SpecialTypeToFill,
+    TransactionToFill, TypeContentToFill,
+};
+
+use crate::call_tree::{
+    self, composite_fields, select_variant, set_account_id, set_bytes, set_from_str,
+    set_sequence_length, set_unsigned,
+};
+use crate::units::unsigned_value;
+
+#[derive(Debug)]
+pub enum Error {
+    Exists(String),
+    Format(String),
+    Io(std::io::Error),
+    InvalidName(String),
+    OtherChain(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Exists(a) => write!(f, "draft {} already exists", a),
+            Error::Format(a) => write!(f, "draft file is damaged: {}", a),
+            Error::Io(a) => write!(f, "draft file: {}", a),
+            Error::InvalidName(a) => write!(f, "invalid draft name {}", a),
+            Error::OtherChain(a) => {
+                write!(f, "draft was made for other chain, genesis hash 0x{}", a)
+            }
+        }
+    }
+}
+
+/// Value of single node of transaction tree, as it was entered
+///
+/// Variants and fields are kept by name, so that draft could be applied to call with reordered
+/// variants or fields. Signatures are not kept, and neither are bit sequences. Primitives other
+/// than unsigned integers and special types are kept as text they are filled from.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Node {
+    Account(Option<String>),
+    Array(Vec<Node>),
+    Bytes(String),
+    Composite(Vec<Field>),
+    Sequence(Vec<Node>),
+    /// Primitive other than unsigned integer, or special type, as it would be typed in
+    Text(String),
+    Tuple(Vec<Node>),
+    /// Unsigned integer, as decimal string: u128 does not fit into json number
+    Unsigned(Option<String>),
+    Variant {
+        name: String,
+        fields: Vec<Field>,
+    },
+    Skipped,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Field {
+    pub name: Option<String>,
+    pub node: Node,
+}
+
+/// Half-built transaction saved to file
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Draft {
+    genesis_hash: String,
+    /// Blake2 hash of encoded metadata the draft was made with
+    metadata_hash: String,
+    author: Node,
+    call: Node,
+    extensions: Vec<Node>,
+}
+
+impl Draft {
+    pub fn new(
+        transaction: &TransactionToFill,
+        metadata: &RuntimeMetadataV15,
+        genesis_hash: H256,
+    ) -> Self {
+        Self {
+            genesis_hash: hex::encode(genesis_hash),
+            metadata_hash: metadata_hash(metadata),
+            author: node(&transaction.author.content),
+            call: node(&transaction.call.content),
+            extensions: transaction
+                .extensions
+                .iter()
+                .map(|a| node(&a.content))
+                .collect(),
+        }
+    }
+
+    pub fn open(dir: &str, name: &str) -> Result<Self, Error> {
+        let file = fs::File::open(path(dir, name)?).map_err(Error::Io)?;
+        serde_json::from_reader(file).map_err(|e| Error::Format(e.to_string()))
+    }
+
+    pub fn exists(dir: &str, name: &str) -> bool {
+        path(dir, name).map(|a| a.exists()).unwrap_or(false)
+    }
+
+    /// Save draft; existing draft of the same name is replaced only if asked to
+    pub fn save(&self, dir: &str, name: &str, overwrite: bool) -> Result<(), Error> {
+        let path = path(dir, name)?;
+        fs::create_dir_all(dir).map_err(Error::Io)?;
+        let content = serde_json::to_vec_pretty(self).map_err(|e| Error::Format(e.to_string()))?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .create_new(!overwrite)
+            .truncate(true)
+            .open(path)
+            .map_err(|e| match e.kind() {
+                ErrorKind::AlreadyExists => Error::Exists(name.trim().to_string()),
+                _ => Error::Io(e),
+            })?;
+        file.write_all(&content).map_err(Error::Io)
+    }
+
+    /// Fill transaction with draft values; gives warnings for everything that did not fit
+    ///
+    /// Drafts are made for one chain, but could outlive runtime upgrades; then call layout is
+    /// checked node by node. Draft of other chain is refused.
+    pub fn apply(
+        &self,
+        transaction: &mut TransactionToFill,
+        metadata: &RuntimeMetadataV15,
+        genesis_hash: H256,
+    ) -> Result<Vec<String>, Error> {
+        if self.genesis_hash != hex::encode(genesis_hash) {
+            return Err(Error::OtherChain(self.genesis_hash.to_owned()));
+        }
+        let mut warnings = Vec::new();
+        if self.metadata_hash != metadata_hash(metadata) {
+            warnings.push(
+                "runtime was upgraded since draft was saved, check the call carefully".to_string(),
+            );
+        }
+        let mut filler = Filler {
+            metadata,
+            warnings: &mut warnings,
+        };
+        filler.apply(&self.author, &mut transaction.author.content, "author");
+        filler.apply(&self.call, &mut transaction.call.content, "call");
+        if self.extensions.len() != transaction.extensions.len() {
+            filler.warn("extensions", "number of extensions changed".to_string());
+        }
+        for (index, (draft, extension)) in self
+            .extensions
+            .iter()
+            .zip(transaction.extensions.iter_mut())
+            .enumerate()
+        {
+            filler.apply(
+                draft,
+                &mut extension.content,
+                &format!("extension {}", index),
+            );
+        }
+        Ok(warnings)
+    }
+}
+
+/// Draft files are `<name>.json` in drafts directory
+fn path(dir: &str, name: &str) -> Result<PathBuf, Error> {
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(Error::InvalidName(name.to_string()));
+    }
+    Ok(PathBuf::from(dir).join(format!("{}.json", name)))
+}
+
+fn metadata_hash(metadata: &RuntimeMetadataV15) -> String {
+    hex::encode(blake2_256(&metadata.encode()))
+}
+
+fn node(content: &TypeContentToFill) -> Node {
+    match content {
+        TypeContentToFill::ArrayU8(a) => Node::Bytes(hex::encode(&a.content)),
+        TypeContentToFill::ArrayRegular(a) => Node::Array(a.content.iter().map(node).collect()),
+        TypeContentToFill::Composite(a) => Node::Composite(fields(a)),
+        TypeContentToFill::Primitive(
+            PrimitiveToFill::CompactUnsigned(a) | PrimitiveToFill::Unsigned(a),
+        ) => Node::Unsigned(unsigned_value(&a.content).map(|b| b.to_string())),
+        TypeContentToFill::SequenceRegular(a) => {
+            Node::Sequence(a.content.iter().map(node).collect())
+        }
+        TypeContentToFill::SequenceU8(a) => Node::Bytes(hex::encode(&a.content)),
+        TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(a)) => {
+            Node::Account(a.as_ref().map(|b| hex::encode(b.0)))
+        }
+        TypeContentToFill::Tuple(a) => Node::Tuple(a.iter().map(|b| node(&b.content)).collect()),
+        TypeContentToFill::Variant(a) => Node::Variant {
+            name: a.selected.name.to_owned(),
+            fields: fields(&a.selected.fields_to_fill),
+        },
+        _ => text(content).map(Node::Text).unwrap_or(Node::Skipped),
+    }
+}
+
+/// Era text for immortal transaction; other eras are period and phase
+const IMMORTAL: &str = "immortal";
+
+/// Primitive or special type, as text it is filled from; `None` if not filled or not kept
+fn text(content: &TypeContentToFill) -> Option<String> {
+    match content {
+        TypeContentToFill::Primitive(PrimitiveToFill::Regular(a)) => match a {
+            RegularPrimitiveToFill::Bool(b) => b.map(|c| c.to_string()),
+            RegularPrimitiveToFill::Char(b) => b.map(|c| c.to_string()),
+            RegularPrimitiveToFill::I8(b) => b.map(|c| c.to_string()),
+            RegularPrimitiveToFill::I16(b) => b.map(|c| c.to_string()),
+            RegularPrimitiveToFill::I32(b) => b.map(|c| c.to_string()),
+            RegularPrimitiveToFill::I64(b) => b.map(|c| c.to_string()),
+            RegularPrimitiveToFill::I128(b) => b.map(|c| c.to_string()),
+            RegularPrimitiveToFill::I256(b) => b.as_ref().map(|c| c.to_string()),
+            RegularPrimitiveToFill::Str(b) => Some(b.to_owned()),
+            RegularPrimitiveToFill::U256(b) => b.as_ref().map(|c| c.to_string()),
+        },
+        TypeContentToFill::SpecialType(a) => match a {
+            SpecialTypeToFill::Era(EraToFill::Immortal) => Some(IMMORTAL.to_string()),
+            SpecialTypeToFill::Era(EraToFill::Mortal {
+                period: Some(period),
+                phase: Some(phase),
+            }) => Some(format!("{} {}", period, phase)),
+            SpecialTypeToFill::H256 { hash, .. } => hash.map(hex::encode),
+            SpecialTypeToFill::PerU16 { value, .. } => value.map(|b| parts(&b)),
+            SpecialTypeToFill::Perbill { value, .. } => value.map(|b| parts(&b)),
+            SpecialTypeToFill::Percent { value, .. } => value.map(|b| parts(&b)),
+            SpecialTypeToFill::Permill { value, .. } => value.map(|b| parts(&b)),
+            SpecialTypeToFill::Perquintill { value, .. } => value.map(|b| parts(&b)),
+            SpecialTypeToFill::PublicEd25519(b) => b.as_ref().map(|c| hex::encode(c.0)),
+            SpecialTypeToFill::PublicSr25519(b) => b.as_ref().map(|c| hex::encode(c.0)),
+            SpecialTypeToFill::PublicEcdsa(b) => b.as_ref().map(|c| hex::encode(c.0)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parts of per-thing; per-things encode as their parts, in unsigned integer of up to 8 bytes
+fn parts<T: Encode>(value: &T) -> String {
+    let mut bytes = [0u8; 8];
+    let encoded = value.encode();
+    bytes[..encoded.len()].copy_from_slice(&encoded);
+    u64::from_le_bytes(bytes).to_string()
+}
+
+fn fields(input: &[FieldToFill]) -> Vec<Field> {
+    input
+        .iter()
+        .map(|a| Field {
+            name: a.field_name.to_owned(),
+            node: node(&a.type_to_fill.content),
+        })
+        .collect()
+}
+
+/// Fill single node, e.g. call decoded elsewhere; warnings are added for parts that did not fit
+pub fn fill(
+    draft: &Node,
+    content: &mut TypeContentToFill,
+    metadata: &RuntimeMetadataV15,
+    path: &str,
+    warnings: &mut Vec<String>,
+) {
+    Filler { metadata, warnings }.apply(draft, content, path)
+}
+
+/// Walks draft and transaction together, collecting warnings with path to node
+struct Filler<'a> {
+    metadata: &'a RuntimeMetadataV15,
+    warnings: &'a mut Vec<String>,
+}
+
+impl<'a> Filler<'a> {
+    fn warn(&mut self, path: &str, warning: String) {
+        self.warnings.push(format!("{}: {}", path, warning));
+    }
+
+    fn check(&mut self, path: &str, result: Result<(), call_tree::Error>) {
+        if let Err(e) = result {
+            self.warn(path, e.to_string());
+        }
+    }
+
+    fn apply(&mut self, draft: &Node, content: &mut TypeContentToFill, path: &str) {
+        match draft {
+            Node::Account(Some(a)) => {
+                let result = match hex::decode(a) {
+                    Ok(b) if b.len() == 32 => {
+                        set_account_id(content, H256::from_slice(&b), self.metadata)
+                    }
+                    _ => Err(call_tree::Error::UnexpectedType(
+                        "32-byte account".to_string(),
+                    )),
+                };
+                self.check(path, result);
+            }
+            Node::Array(a) => match content {
+                TypeContentToFill::ArrayRegular(b) if b.content.len() == a.len() => {
+                    for (index, (draft, element)) in a.iter().zip(b.content.iter_mut()).enumerate()
+                    {
+                        self.apply(draft, element, &format!("{}/{}", path, index));
+                    }
+                }
+                _ => self.warn(path, format!("expected array of {} elements", a.len())),
+            },
+            Node::Bytes(a) => {
+                let result = match hex::decode(a) {
+                    Ok(b) => set_bytes(content, &b),
+                    Err(e) => Err(call_tree::Error::UnexpectedType(e.to_string())),
+                };
+                self.check(path, result);
+            }
+            Node::Composite(a) => match composite_fields(content) {
+                Ok(b) => self.apply_fields(a, b, path),
+                Err(e) => self.warn(path, e.to_string()),
+            },
+            Node::Sequence(a) => match set_sequence_length(content, a.len(), self.metadata) {
+                Ok(b) => {
+                    for (index, (draft, element)) in a.iter().zip(b.iter_mut()).enumerate() {
+                        self.apply(draft, element, &format!("{}/{}", path, index));
+                    }
+                }
+                Err(e) => self.warn(path, e.to_string()),
+            },
+            Node::Text(a) => match content {
+                TypeContentToFill::SpecialType(SpecialTypeToFill::Era(b)) if a == IMMORTAL => {
+                    *b = EraToFill::Immortal
+                }
+                TypeContentToFill::Primitive(PrimitiveToFill::Regular(_))
+                | TypeContentToFill::SpecialType(_) => {
+                    let result = set_from_str(content, a);
+                    self.check(path, result);
+                }
+                _ => self.warn(path, format!("no primitive for {}", a)),
+            },
+            Node::Tuple(a) => match content {
+                TypeContentToFill::Tuple(b) if b.len() == a.len() => {
+                    for (index, (draft, element)) in a.iter().zip(b.iter_mut()).enumerate() {
+                        self.apply(draft, &mut element.content, &format!("{}/{}", path, index));
+                    }
+                }
+                _ => self.warn(path, format!("expected tuple of {} elements", a.len())),
+            },
+            Node::Unsigned(Some(a)) => {
+                let result = match a.parse::<u128>() {
+                    Ok(b) => set_unsigned(content, b),
+                    Err(e) => Err(call_tree::Error::UnexpectedType(e.to_string())),
+                };
+                self.check(path, result);
+            }
+            Node::Variant { name, fields } => {
+                let path = format!("{}/{}", path, name);
+                match select_variant(content, name, self.metadata) {
+                    Ok(b) => self.apply_fields(fields, b, &path),
+                    Err(e) => self.warn(&path, e.to_string()),
+                }
+            }
+            Node::Account(None) | Node::Unsigned(None) | Node::Skipped => {}
+        }
+    }
+
+    /// Named fields are matched by name, unnamed ones by position
+    fn apply_fields(&mut self, draft: &[Field], fields: &mut [FieldToFill], path: &str) {
+        for (index, field) in draft.iter().enumerate() {
+            let target = match field.name {
+                Some(ref name) => fields
+                    .iter_mut()
+                    .find(|a| a.field_name.as_deref() == Some(name)),
+                None => fields.get_mut(index).filter(|a| a.field_name.is_none()),
+            };
+            let label = field.name.clone().unwrap_or(index.to_string());
+            match target {
+                Some(a) => self.apply(
+                    &field.node,
+                    &mut a.type_to_fill.content,
+                    &format!("{}/{}", path, label),
+                ),
+                None => self.warn(path, format!("field {} is gone", label)),
+            }
+        }
+        for (index, field) in fields.iter().enumerate() {
+            let known = match field.field_name {
+                Some(ref name) => draft.iter().any(|a| a.name.as_ref() == Some(name)),
+                None => index < draft.len(),
+            };
+            if !known {
+                let label = field.field_name.clone().unwrap_or(index.to_string());
+                self.warn(path, format!("new field {} is not filled", label));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use frame_metadata::v15::{CustomMetadata, ExtrinsicMetadata, OuterEnums};
+    use scale_info::meta_type;
+
+    /// Metadata without types; text nodes do not look into it
+    fn metadata() -> RuntimeMetadataV15 {
+        RuntimeMetadataV15::new(
+            Vec::new(),
+            ExtrinsicMetadata {
+                version: 4,
+                address_ty: meta_type::<()>(),
+                call_ty: meta_type::<()>(),
+                signature_ty: meta_type::<()>(),
+                extra_ty: meta_type::<()>(),
+                signed_extensions: Vec::new(),
+            },
+            meta_type::<()>(),
+            Vec::new(),
+            OuterEnums {
+                call_enum_ty: meta_type::<()>(),
+                event_enum_ty: meta_type::<()>(),
+                error_enum_ty: meta_type::<()>(),
+            },
+            CustomMetadata {
+                map: Default::default(),
+            },
+        )
+    }
+
+    /// Save filled value to draft node and fill other value from it
+    fn round_trip(filled: &TypeContentToFill, mut other: TypeContentToFill) -> TypeContentToFill {
+        let draft = node(filled);
+        assert!(matches!(draft, Node::Text(_)), "{:?}", draft);
+        let mut warnings = Vec::new();
+        fill(&draft, &mut other, &metadata(), "value", &mut warnings);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        other
+    }
+
+    #[test]
+    fn primitives_kept_as_text() {
+        let i8_field = |a| {
+            TypeContentToFill::Primitive(PrimitiveToFill::Regular(RegularPrimitiveToFill::I8(a)))
+        };
+        let filled = round_trip(&i8_field(Some(-5)), i8_field(None));
+        assert_eq!(text(&filled).as_deref(), Some("-5"));
+    }
+
+    #[test]
+    fn special_types_kept_as_text() {
+        let percent = || {
+            TypeContentToFill::SpecialType(SpecialTypeToFill::Percent {
+                value: None,
+                is_compact: false,
+            })
+        };
+        let mut filled = percent();
+        set_from_str(&mut filled, "42").unwrap();
+        let filled = round_trip(&filled, percent());
+        assert_eq!(text(&filled).as_deref(), Some("42"));
+
+        let era = |a| TypeContentToFill::SpecialType(SpecialTypeToFill::Era(a));
+        let mortal = era(EraToFill::Mortal {
+            period: Some(64),
+            phase: Some(3),
+        });
+        let filled = round_trip(&era(EraToFill::Immortal), mortal.clone());
+        assert_eq!(text(&filled).as_deref(), Some(IMMORTAL));
+        let filled = round_trip(&mortal, era(EraToFill::Immortal));
+        assert_eq!(text(&filled).as_deref(), Some("64 3"));
+    }
+}
@@ -0,0 +1,155 @@
+use std::fs;
+
+#[derive(Debug)]
+pub enum Error {
+    Clipboard(arboard::Error),
+    Io(std::io::Error),
+    InvalidHex(String),
+    UnknownKind(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Clipboard(a) => write!(f, "clipboard: {}", a),
+            Error::Io(a) => write!(f, "export file: {}", a),
+            Error::InvalidHex(a) => write!(f, "invalid hex in file: {}", a),
+            Error::UnknownKind(a) => write!(
+                f,
+                "unknown export {}, expected call, hash, signable or signed",
+                a
+            ),
+        }
+    }
+}
+
+/// What part of transaction to export
+#[derive(Clone, Copy, Debug)]
+pub enum Kind {
+    /// Encoded call
+    Call,
+    /// Blake2-256 hash of encoded call, as multisig and preimage pallets use it
+    Hash,
+    /// Payload to sign, as is
+    Signable,
+    /// Signed extrinsic, ready to submit
+    Signed,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Call => "call data",
+            Kind::Hash => "call hash",
+            Kind::Signable => "signable payload",
+            Kind::Signed => "signed extrinsic",
+        }
+    }
+}
+
+/// `call`, `hash`, `signable` or `signed`, with optional file name; without one export goes
+/// to clipboard
+pub fn parse_request(input: &str) -> Result<(Kind, Option<String>), Error> {
+    let input = input.trim();
+    // file name is the rest of input, spaces in it are kept as typed
+    let (kind, file) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let kind = match kind {
+        "call" => Kind::Call,
+        "hash" => Kind::Hash,
+        "signable" => Kind::Signable,
+        "signed" => Kind::Signed,
+        a => return Err(Error::UnknownKind(a.to_string())),
+    };
+    let file = file.trim();
+    Ok((kind, (!file.is_empty()).then(|| file.to_string())))
+}
+
+/// Clipboard is opened at first copy and kept open: on some platforms copied text lives only
+/// while clipboard is held
+#[derive(Default)]
+pub struct Clipboard(Option<arboard::Clipboard>);
+
+impl Clipboard {
+    pub fn copy(&mut self, text: &str) -> Result<(), Error> {
+        let clipboard = match self.0 {
+            Some(ref mut a) => a,
+            None => self
+                .0
+                .insert(arboard::Clipboard::new().map_err(Error::Clipboard)?),
+        };
+        clipboard.set_text(text).map_err(Error::Clipboard)
+    }
+}
+
+/// Write `0x`-prefixed hex to file or clipboard; gives where it went
+pub fn export(data: &[u8], file: Option<&str>, clipboard: &mut Clipboard) -> Result<String, Error> {
+    let text = format!("0x{}", hex::encode(data));
+    match file {
+        Some(a) => {
+            fs::write(a, text + "\n").map_err(Error::Io)?;
+            Ok(a.to_string())
+        }
+        None => {
+            clipboard.copy(&text)?;
+            Ok("clipboard".to_string())
+        }
+    }
+}
+
+/// Read back exported hex, `0x`-prefixed or not
+pub fn read(file: &str) -> Result<Vec<u8>, Error> {
+    let text = fs::read_to_string(file).map_err(Error::Io)?;
+    hex::decode(text.trim().trim_start_matches("0x")).map_err(|e| Error::InvalidHex(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// File in temporary directory, with nothing there yet
+    fn path(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("lempi-export-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn requests() {
+        assert!(matches!(parse_request("call"), Ok((Kind::Call, None))));
+        assert!(matches!(parse_request(" hash  "), Ok((Kind::Hash, None))));
+        assert!(matches!(
+            parse_request("signable payload.hex"),
+            Ok((Kind::Signable, Some(a))) if a == "payload.hex"
+        ));
+        assert!(matches!(
+            parse_request("signed  my  signed tx.hex "),
+            Ok((Kind::Signed, Some(a))) if a == "my  signed tx.hex"
+        ));
+        assert!(matches!(
+            parse_request("calls file"),
+            Err(Error::UnknownKind(a)) if a == "calls"
+        ));
+        assert!(matches!(parse_request("Call"), Err(Error::UnknownKind(_))));
+        assert!(matches!(parse_request(""), Err(Error::UnknownKind(_))));
+    }
+
+    #[test]
+    fn file_read_back() {
+        let file = path("call with spaces.hex");
+        let mut clipboard = Clipboard::default();
+        assert_eq!(
+            export(&[0x05, 0x00, 0xff], Some(&file), &mut clipboard).unwrap(),
+            file
+        );
+        assert_eq!(fs::read_to_string(&file).unwrap(), "0x0500ff\n");
+        assert_eq!(read(&file).unwrap(), [0x05, 0x00, 0xff]);
+
+        fs::write(&file, " 0500ff \n").unwrap();
+        assert_eq!(read(&file).unwrap(), [0x05, 0x00, 0xff]);
+        fs::write(&file, "0x0500f").unwrap();
+        assert!(matches!(read(&file), Err(Error::InvalidHex(_))));
+        fs::remove_file(&file).unwrap();
+        assert!(matches!(read(&file), Err(Error::Io(_))));
+    }
+}
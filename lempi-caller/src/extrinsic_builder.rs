@@ -1,57 +1,197 @@
 use frame_metadata::v15::RuntimeMetadataV15;
 
-use primitive_types::H256;
+use parity_scale_codec::{Decode, Encode};
+
+use primitive_types::{H160, H256};
 
 use serde_json::{Map, Value};
 
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use substrate_constructor::fill_prepare::{
-    EraToFill, PrimitiveToFill, SpecialTypeToFill, TransactionToFill, TypeContentToFill,
-    TypeToFill, VariantSelector,
+    EraToFill, FieldToFill, PrimitiveToFill, SpecialTypeToFill, TransactionToFill,
+    TypeContentToFill, TypeToFill, UnsignedToFill, VariantSelector,
 };
 use substrate_constructor::finalize::Finalize;
-use substrate_constructor::try_fill::{TryBytesFill, TryFill};
 
-use substrate_parser::additional_types::SignatureSr25519;
+use substrate_parser::additional_types::{
+    AccountId32, SignatureEcdsa, SignatureEd25519, SignatureSr25519,
+};
+use substrate_parser::decode_as_call_unmarked;
+
+use sp_core::blake2_256;
+
+use std::fs;
 
-use crate::author::AddressBook;
+use crate::author::{labelled, AccountId, Address, AddressBook, Crypto, Signature};
+use crate::bulk::{existential_deposit, read_transfers, Transfer, Transfers};
+use crate::call_tree::{
+    composite_fields, field, only_field, select_variant, set_account_id, set_account_ids,
+    set_bytes, set_from_str, set_option, set_sequence_length, set_unsigned,
+};
+use crate::decode::report;
+use crate::draft::{self, Draft};
+use crate::export::{self, Clipboard, Kind};
+use crate::import::import;
+use crate::multisig::{Multisig, Pending, Timepoint};
+use crate::proxy::{self, Proxies};
+use crate::search::{fuzzy_score, CallEntry};
+use crate::units::{balance_candidate, is_balance_type_name, unsigned_value, Units};
+use crate::uos;
+use crate::validation::{account, bytes, check};
 
 #[derive(Clone)]
 pub struct Selector {
     pub list: Vec<String>,
     index: usize,
+    /// Typed text narrowing the list
+    filter: String,
+    /// Indices of list items matching filter
+    visible: Vec<usize>,
 }
 
 impl Selector {
     pub fn new(list: Vec<String>, index: usize) -> Self {
         assert!(index < list.len());
-        Self { list, index }
+        let visible = (0..list.len()).collect();
+        Self {
+            list,
+            index,
+            filter: String::new(),
+            visible,
+        }
     }
 
+    /// Index of selected item in full list
     pub fn index(&self) -> usize {
         self.index
     }
 
     pub fn inc(&mut self) {
-        if self.index < self.list.len() - 1 {
-            self.index += 1
+        if let Some(position) = self.visible.iter().position(|a| *a == self.index) {
+            if let Some(a) = self.visible.get(position + 1) {
+                self.index = *a
+            }
         }
     }
 
     pub fn dec(&mut self) {
-        if self.index > 0 {
-            self.index -= 1
+        if let Some(position) = self.visible.iter().position(|a| *a == self.index) {
+            if position > 0 {
+                self.index = self.visible[position - 1]
+            }
         }
     }
 
     pub fn selected(&self) -> String {
         self.list[self.index].to_string()
     }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// Items matching filter, with their indices in full list
+    pub fn visible(&self) -> impl Iterator<Item = (usize, &String)> {
+        self.visible.iter().map(|a| (*a, &self.list[*a]))
+    }
+
+    /// Whether anything matches filter; selected item is meaningless otherwise
+    pub fn is_empty(&self) -> bool {
+        self.visible.is_empty()
+    }
+
+    pub fn filter_push(&mut self, c: char) {
+        self.filter.push(c);
+        self.apply_filter();
+    }
+
+    pub fn filter_pop(&mut self) {
+        let _ = self.filter.pop();
+        self.apply_filter();
+    }
+
+    /// Fuzzy match, best matches first; selection jumps to best match
+    fn apply_filter(&mut self) {
+        let mut scored: Vec<((usize, usize), usize)> = self
+            .list
+            .iter()
+            .enumerate()
+            .filter_map(|(index, a)| fuzzy_score(&self.filter, a).map(|score| (score, index)))
+            .collect();
+        scored.sort();
+        self.visible = scored.into_iter().map(|(_, index)| index).collect();
+        if !self.filter.is_empty() || !self.visible.contains(&self.index) {
+            if let Some(a) = self.visible.first() {
+                self.index = *a
+            }
+        }
+    }
 }
 
-pub struct Builder<'a, 'b> {
-    address_book: &'b AddressBook,
+/// Undo steps kept
+const HISTORY_DEPTH: usize = 100;
+
+/// Wrapping waiting for chain data longer than this is given up
+const WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Everything that edits change: transaction and calls put aside for batch
+#[derive(Debug)]
+struct Snapshot {
+    transaction: TransactionToFill,
+    batch: Vec<TypeContentToFill>,
+    batch_editing: Option<(usize, TypeContentToFill)>,
+}
+
+impl Snapshot {
+    /// Constructor types have no `PartialEq`; their debug print shows every value
+    fn same_as(&self, other: &Snapshot) -> bool {
+        format!("{:?}", self) == format!("{:?}", other)
+    }
+}
+
+/// Earlier states to undo to, and undone ones to redo
+#[derive(Default)]
+struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    /// State before an edit; undone states can not be redone after new edit
+    fn record(&mut self, state: Snapshot) {
+        if self.undo.len() == HISTORY_DEPTH {
+            self.undo.remove(0);
+        }
+        self.undo.push(state);
+        self.redo.clear();
+    }
+}
+
+/// Call waiting for chain data to be wrapped into multisig call
+struct MultisigWrap {
+    multisig: Multisig,
+    /// Signatory sending transaction
+    me: H256,
+    /// Send only call hash, with `approve_as_multi`
+    approve_only: bool,
+    call: Vec<u8>,
+    call_hash: H256,
+    started: Instant,
+}
+
+/// Call waiting for delegations of real account to be wrapped into proxy call
+struct ProxyWrap {
+    real: H256,
+    /// Delegate sending transaction
+    me: H256,
+    /// Proxy type name; any delegation of author if not set
+    proxy_type: Option<String>,
+    started: Instant,
+}
+
+pub struct Builder<'a> {
     buffer: String,
     pub details: bool,
     genesis_hash: H256,
@@ -61,15 +201,31 @@ pub struct Builder<'a, 'b> {
     specs: Map<String, Value>,
     pub ss58: u16,
     transaction: TransactionToFill,
+    units: Units,
+    /// QR code of payload waiting for external signature, and crypto of key asked to sign it
+    uos: Option<(String, Crypto)>,
+    /// Where payload for external signer is written
+    payload_file: String,
+    /// Directory with saved drafts
+    drafts_dir: String,
+    clipboard: Clipboard,
+    multisig: Option<MultisigWrap>,
+    proxy: Option<ProxyWrap>,
+    history: History,
+    /// Calls collected for `Utility` batch, in order
+    batch: Vec<TypeContentToFill>,
+    /// Batched call taken out to edit, with call that was current before it
+    batch_editing: Option<(usize, TypeContentToFill)>,
     log: Vec<String>,
 }
 
-impl<'a, 'b> Builder<'a, 'b> {
+impl<'a> Builder<'a> {
     pub fn new(
         metadata: &'a RuntimeMetadataV15,
-        address_book: &'b AddressBook,
         genesis_hash: H256,
         specs: Map<String, Value>,
+        payload_file: String,
+        drafts_dir: String,
     ) -> Self {
         let mut transaction = TransactionToFill::init(&mut (), metadata, genesis_hash).unwrap();
         let ss58 = if let Some(Value::Number(a)) = specs.get("ss58Format") {
@@ -81,8 +237,12 @@ impl<'a, 'b> Builder<'a, 'b> {
         } else {
             42
         };
+        let mut log = Vec::new();
+        let units = Units::from_specs(&specs).unwrap_or_else(|e| {
+            log.push(format!("{}; balances are in planck", e));
+            Units::planck()
+        });
         Self {
-            address_book,
             buffer: "".to_owned(),
             details: false,
             genesis_hash,
@@ -92,40 +252,82 @@ impl<'a, 'b> Builder<'a, 'b> {
             specs,
             ss58,
             transaction,
-            log: Vec::new(),
+            units,
+            uos: None,
+            payload_file,
+            drafts_dir,
+            clipboard: Clipboard::default(),
+            multisig: None,
+            proxy: None,
+            history: History::default(),
+            batch: Vec::new(),
+            batch_editing: None,
+            log,
         }
     }
 
-    pub fn call(&self) -> Vec<Card> {
-        let mut output = steamroller(&self.transaction.author, 0, self.ss58);
-        output.append(&mut steamroller(&self.transaction.call, 0, self.ss58));
+    pub fn call(&self, address_book: &AddressBook) -> Vec<Card> {
+        self.cards(Some(address_book))
+    }
+
+    /// Flat list of cards; without address book only values are shown, not names
+    fn cards(&self, address_book: Option<&AddressBook>) -> Vec<Card> {
+        let lookup = self.lookup(address_book);
+        let mut output = steamroller(&self.transaction.author, 0, &lookup);
+        output.append(&mut steamroller(&self.transaction.call, 0, &lookup));
         for extension in &self.transaction.extensions {
-            output.append(&mut steamroller(&extension, 0, self.ss58));
+            output.append(&mut steamroller(&extension, 0, &lookup));
         }
-        output.append(&mut steamroller(&self.transaction.signature, 0, self.ss58));
+        output.append(&mut steamroller(&self.transaction.signature, 0, &lookup));
         output
     }
 
-    pub fn author(&self) -> Option<H256> {
+    fn lookup<'c>(&'c self, address_book: Option<&'c AddressBook>) -> Lookup<'c> {
+        Lookup {
+            ss58: self.ss58,
+            units: &self.units,
+            address_book,
+        }
+    }
+
+    pub fn author(&self) -> Option<AccountId> {
         if let Some(a) = self.transaction.author_as_sr25519_compatible() {
-            Some(H256::from(a))
+            Some(AccountId::Id32(H256::from(a)))
         } else {
-            None
+            find_array_u8(&self.transaction.author.content, 20)
+                .map(|a| AccountId::Id20(H160::from_slice(a)))
         }
     }
 
-    pub fn details(&self) -> DetailsCard {
+    /// Position of first card of signature
+    fn signature_position(&self) -> usize {
+        self.cards(None).len()
+            - steamroller(&self.transaction.signature, 0, &self.lookup(None)).len()
+    }
+
+    pub fn details(&self, address_book: &AddressBook) -> DetailsCard {
         let buffer = if self.details {
             Some(self.buffer.clone())
         } else {
             None
         };
-        DetailsCard::new(
+        let mut card = DetailsCard::new(
             self.observable_field(),
             buffer,
             self.selector.clone(),
-            self.address_book,
-        )
+            address_book,
+            &self.units,
+        );
+        if self.position >= self.signature_position() {
+            card.preview = None;
+        }
+        if let Some((ref qr, _)) = self.uos {
+            card.content = format!(
+                "{}\r\nScan with external signer, payload is also written to {}\r\n\r\nPaste signature hex and press Enter",
+                qr, self.payload_file
+            );
+        }
+        card
     }
 
     pub fn up(&mut self) {
@@ -135,7 +337,7 @@ impl<'a, 'b> Builder<'a, 'b> {
             }
         } else {
             if self.position > 0 {
-                self.position -= 1;
+                self.set_position(self.position - 1);
             }
         }
     }
@@ -146,121 +348,325 @@ impl<'a, 'b> Builder<'a, 'b> {
                 a.inc()
             }
         } else {
-            if self.position < self.call().len() - 1 {
-                self.position += 1;
+            if self.position < self.cards(None).len() - 1 {
+                self.set_position(self.position + 1);
             }
         }
     }
 
     pub fn left(&mut self) {
+        if self.refuse_while_waiting() {
+            return;
+        }
+        let before = self.snapshot();
         let types = &self.metadata.types;
-        match self.modifiable_field().content {
-            TypeContentToFill::SequenceRegular(ref mut a) => a.remove_last_element(),
-            TypeContentToFill::SpecialType(SpecialTypeToFill::Era(ref mut a)) => a.selector(),
-            TypeContentToFill::Variant(ref mut a) => a
-                .selector_up::<(), RuntimeMetadataV15>(&mut (), types)
-                .unwrap(),
-            _ => (),
+        let changed = match self.modifiable_field().content {
+            TypeContentToFill::SequenceRegular(ref mut a) => {
+                a.remove_last_element();
+                true
+            }
+            TypeContentToFill::SpecialType(SpecialTypeToFill::Era(ref mut a)) => {
+                a.selector();
+                true
+            }
+            TypeContentToFill::Variant(ref mut a) => {
+                a.selector_up::<(), RuntimeMetadataV15>(&mut (), types)
+                    .unwrap();
+                true
+            }
+            _ => false,
         };
+        if changed {
+            self.record_edit(before);
+            self.uos = None;
+        }
     }
 
     pub fn right(&mut self) {
+        if self.refuse_while_waiting() {
+            return;
+        }
+        let before = self.snapshot();
         let types = &self.metadata.types;
-        match self.modifiable_field().content {
-            TypeContentToFill::SequenceRegular(ref mut a) => a
-                .add_new_element::<(), RuntimeMetadataV15>(&mut (), types)
-                .unwrap(),
-            TypeContentToFill::SpecialType(SpecialTypeToFill::Era(ref mut a)) => a.selector(),
-            TypeContentToFill::Variant(ref mut a) => a
-                .selector_down::<(), RuntimeMetadataV15>(&mut (), types)
-                .unwrap(),
-            _ => (),
+        let changed = match self.modifiable_field().content {
+            TypeContentToFill::SequenceRegular(ref mut a) => {
+                a.add_new_element::<(), RuntimeMetadataV15>(&mut (), types)
+                    .unwrap();
+                true
+            }
+            TypeContentToFill::SpecialType(SpecialTypeToFill::Era(ref mut a)) => {
+                a.selector();
+                true
+            }
+            TypeContentToFill::Variant(ref mut a) => {
+                a.selector_down::<(), RuntimeMetadataV15>(&mut (), types)
+                    .unwrap();
+                true
+            }
+            _ => false,
         };
+        if changed {
+            self.record_edit(before);
+            self.uos = None;
+        }
+    }
+
+    /// Go back to transaction before last edit
+    pub fn undo(&mut self) {
+        if self.is_waiting() {
+            self.log
+                .push("wrapping is still waiting for chain".to_string());
+            return;
+        }
+        match self.history.undo.pop() {
+            Some(a) => {
+                let current = self.snapshot();
+                self.transaction = a.transaction;
+                self.batch = a.batch;
+                self.batch_editing = a.batch_editing;
+                self.history.redo.push(current);
+                self.reset_view();
+            }
+            None => self.log.push("nothing to undo".to_string()),
+        }
+    }
+
+    /// Return to undone edit
+    pub fn redo(&mut self) {
+        if self.is_waiting() {
+            self.log
+                .push("wrapping is still waiting for chain".to_string());
+            return;
+        }
+        match self.history.redo.pop() {
+            Some(a) => {
+                let current = self.snapshot();
+                self.transaction = a.transaction;
+                self.batch = a.batch;
+                self.batch_editing = a.batch_editing;
+                self.history.undo.push(current);
+                self.reset_view();
+            }
+            None => self.log.push("nothing to redo".to_string()),
+        }
+    }
+
+    /// Keep state from before edit for undo, unless edit changed nothing
+    fn record_edit(&mut self, before: Snapshot) {
+        if !before.same_as(&self.snapshot()) {
+            self.history.record(before);
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            transaction: self.transaction.clone(),
+            batch: self.batch.clone(),
+            batch_editing: self.batch_editing.clone(),
+        }
     }
 
-    pub fn enter(&mut self) {
+    /// Leave editing, keeping position if it is still in the tree
+    fn reset_view(&mut self) {
+        self.set_position(self.position.min(self.cards(None).len() - 1));
+        self.selector = None;
+        self.details = false;
+        self.buffer = "".to_string();
+        self.uos = None;
+    }
+
+    pub fn enter(&mut self, address_book: &AddressBook) {
+        if self.refuse_while_waiting() {
+            return;
+        }
         if self.details {
             let buffer = self.buffer.clone();
-            let types = &self.metadata.types;
             let selector = self.selector.clone();
-            let address_book = self.address_book;
-            let author = self.author();
-            let signable = self.signable().clone();
-            match self.modifiable_field().content {
-                TypeContentToFill::ArrayU8(ref mut a) => {
-                    a.upd_from_utf8(&buffer);
-                }
-                TypeContentToFill::Primitive(ref mut a) => match a {
-                    PrimitiveToFill::CompactUnsigned(ref mut b) => b.content.upd_from_str(&buffer),
-                    PrimitiveToFill::Regular(ref mut b) => b.upd_from_str(&buffer),
-                    PrimitiveToFill::Unsigned(ref mut b) => b.content.upd_from_str(&buffer),
-                },
-                TypeContentToFill::SequenceRegular(ref mut a) => {
-                    if let Ok(number) = usize::from_str(&buffer) {
-                        a.set_number_of_elements::<(), RuntimeMetadataV15>(&mut (), types, number)
-                            .unwrap()
+            // accounts could be typed as SS58 when address book has no match
+            let (typed_account, invalid) = {
+                let observed = self.observable_field();
+                let typed_account = matches!(
+                    observed.content,
+                    TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(_))
+                );
+                let typed = match selector {
+                    Some(ref a) if a.is_empty() && typed_account => Some(a.filter()),
+                    Some(_) => None,
+                    None => Some(&buffer[..]),
+                };
+                let invalid = match typed {
+                    Some(a) if !observed.balance && self.position < self.signature_position() => {
+                        check(observed.content, a).err()
+                    }
+                    _ => None,
+                };
+                (typed_account, invalid)
+            };
+            if let Some(e) = invalid {
+                self.log.push(format!("not set: {}", e));
+                return;
+            }
+            if !typed_account && selector.as_ref().map(|a| a.is_empty()).unwrap_or(false) {
+                self.log
+                    .push("nothing matches the filter, nothing selected".to_string());
+                return;
+            }
+            let before = self.snapshot();
+            let types = &self.metadata.types;
+            let units = self.units.clone();
+            let in_signature = self.position >= self.signature_position();
+            let field = self.modifiable_field();
+            let signature_slot = match field.content {
+                TypeContentToFill::SpecialType(
+                    SpecialTypeToFill::SignatureSr25519(_)
+                    | SpecialTypeToFill::SignatureEd25519(_)
+                    | SpecialTypeToFill::SignatureEcdsa(_),
+                ) => true,
+                // `EthereumSignature` is just 65 bytes
+                TypeContentToFill::ArrayU8(ref a) => in_signature && a.len == 65,
+                _ => false,
+            };
+            if signature_slot {
+                if let Some((_, crypto)) = self.uos {
+                    match uos::parse_signature(&buffer, crypto) {
+                        Ok(a) => self.put_signature(a),
+                        Err(e) => {
+                            self.log.push(e.to_string());
+                            return;
+                        }
+                    }
+                    self.uos = None;
+                } else {
+                    self.sign(address_book);
+                    if self.uos.is_some() {
+                        self.buffer = "".to_string();
+                        return;
                     }
                 }
-                TypeContentToFill::SequenceU8(ref mut a) => {
-                    a.upd_from_utf8(&buffer);
+                self.record_edit(before);
+                self.buffer = "".to_string();
+                self.selector = None;
+                self.details = false;
+                return;
+            }
+            if field.balance {
+                let set = units
+                    .parse(&buffer)
+                    .map_err(|e| e.to_string())
+                    .and_then(|planck| {
+                        set_unsigned(field.content, planck)
+                            .map(|_| planck)
+                            .map_err(|e| e.to_string())
+                    });
+                match set {
+                    Ok(planck) => self.log.push(format!(
+                        "balance set to {} ({} planck)",
+                        units.format(planck),
+                        planck
+                    )),
+                    Err(e) => self.log.push(format!("balance not set: {}", e)),
                 }
-                TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(ref mut a)) => {
-                    if let Some(s) = selector {
-                        *a = address_book.account_id32(s.index)
+                self.record_edit(before);
+                self.buffer = "".to_string();
+                self.selector = None;
+                self.details = false;
+                return;
+            }
+            let set = match (&mut *field.content, selector) {
+                (TypeContentToFill::ArrayU8(a), Some(s)) if a.len == 20 => {
+                    match address_book.account_id20(s.index) {
+                        Some(b) => {
+                            a.content = b.0.to_vec();
+                            Ok(())
+                        }
+                        None => Err("not a 20-byte account".to_string()),
                     }
                 }
-                TypeContentToFill::SpecialType(SpecialTypeToFill::SignatureSr25519(ref mut a)) => {
-                    if let Some(s) = author {
-                        if let Some(pos) =
-                            address_book.authors().iter().position(|a| a.public() == s)
-                        {
-                            if let Some(signable) = signable {
-                                if let Some(signature) = address_book.authors()[pos].sign(&signable)
-                                {
-                                    *a = Some(SignatureSr25519(signature.0));
-                                }
-                            }
+                // empty text leaves field as it is
+                (
+                    TypeContentToFill::ArrayU8(_)
+                    | TypeContentToFill::Primitive(_)
+                    | TypeContentToFill::SpecialType(_),
+                    None,
+                ) if buffer.trim().is_empty() => Ok(()),
+                (
+                    content @ (TypeContentToFill::ArrayU8(_) | TypeContentToFill::SequenceU8(_)),
+                    _,
+                ) => bytes(&buffer)
+                    .map_err(|e| e.to_string())
+                    .and_then(|b| set_bytes(content, &b).map_err(|e| e.to_string())),
+                (TypeContentToFill::SequenceRegular(a), _) => {
+                    match usize::from_str(buffer.trim()) {
+                        Ok(number) => {
+                            a.set_number_of_elements::<(), RuntimeMetadataV15>(
+                                &mut (),
+                                types,
+                                number,
+                            )
+                            .unwrap();
+                            Ok(())
                         }
+                        Err(e) => Err(e.to_string()),
                     }
                 }
-                TypeContentToFill::SpecialType(ref mut a) => {
-                    a.upd_from_str(&buffer);
-                }
-                TypeContentToFill::Variant(ref mut a) => {
-                    if let Some(s) = selector {
-                        match VariantSelector::new_at::<(), RuntimeMetadataV15>(
-                            &a.available_variants,
-                            &mut (),
-                            types,
-                            s.index,
-                        ) {
-                            Ok(b) => *a = b,
-                            _ => (),
+                (TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(a)), selector) => {
+                    let typed = match selector {
+                        Some(s) if !s.is_empty() => {
+                            *a = address_book.account_id32(s.index);
+                            None
+                        }
+                        Some(s) => Some(s.filter().to_string()),
+                        None => Some(buffer),
+                    };
+                    match typed.map(|b| account(&b)) {
+                        Some(Ok(b)) => {
+                            *a = Some(AccountId32(b.0));
+                            Ok(())
                         }
+                        Some(Err(e)) => Err(e.to_string()),
+                        None => Ok(()),
                     }
                 }
-                _ => {}
+                (
+                    content @ (TypeContentToFill::Primitive(_) | TypeContentToFill::SpecialType(_)),
+                    _,
+                ) => set_from_str(content, buffer.trim()).map_err(|e| e.to_string()),
+                (TypeContentToFill::Variant(a), Some(s)) => {
+                    VariantSelector::new_at::<(), RuntimeMetadataV15>(
+                        &a.available_variants,
+                        &mut (),
+                        types,
+                        s.index,
+                    )
+                    .map(|b| *a = b)
+                    .map_err(|e| format!("{:?}", e))
+                }
+                _ => Ok(()),
+            };
+            if let Err(e) = set {
+                self.log.push(format!("not set: {}", e));
             }
+            self.record_edit(before);
 
             self.buffer = "".to_string();
             self.selector = None;
             self.details = false;
         } else {
             self.selector = match &self.observable_field().content {
+                // Probably `AccountId20`
+                TypeContentToFill::ArrayU8(a) if a.len == 20 => account_selector(address_book),
                 TypeContentToFill::ArrayU8(a) => None,
                 TypeContentToFill::SequenceU8(a) => None,
                 TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(a)) => {
-                    Some(Selector {
-                        list: self.address_book.author_names(),
-                        index: 0,
-                    })
+                    account_selector(address_book)
                 }
                 TypeContentToFill::Variant(a) => {
                     let mut list = Vec::new();
                     for variant in &a.available_variants {
                         list.push(variant.name.clone());
                     }
-                    Some(Selector { list, index: 0 })
+                    let index = list.iter().position(|b| *b == a.selected.name).unwrap_or(0);
+                    Some(Selector::new(list, index))
                 }
                 _ => None,
             };
@@ -270,32 +676,749 @@ impl<'a, 'b> Builder<'a, 'b> {
         }
     }
 
+    /// Typed text goes to selector filter, if there is selector, and to buffer otherwise
     pub fn input(&mut self, c: char) {
-        self.buffer.push(c);
+        match self.selector {
+            Some(ref mut a) => a.filter_push(c),
+            None => self.buffer.push(c),
+        }
     }
 
     pub fn backspace(&mut self) {
-        let _ = self.buffer.pop();
+        match self.selector {
+            Some(ref mut a) => a.filter_pop(),
+            None => {
+                let _ = self.buffer.pop();
+            }
+        }
     }
 
     pub fn paste(&mut self, s: String) {
-        self.buffer.push_str(&s);
+        if self.refuse_while_waiting() {
+            return;
+        }
+        match self.selector {
+            Some(ref mut a) => s.chars().for_each(|c| a.filter_push(c)),
+            None => self.buffer.push_str(&s),
+        }
     }
 
     pub fn position(&self) -> usize {
         self.position
     }
 
+    /// Move to card; payload shown for external signer belongs to the card it was made on
+    fn set_position(&mut self, position: usize) {
+        if position != self.position {
+            self.uos = None;
+        }
+        self.position = position;
+    }
+
+    /// Sign with author key and put signature into matching `MultiSignature` variant
+    ///
+    /// For watch-only author payload goes to external signer instead.
+    fn sign(&mut self, address_book: &AddressBook) {
+        let author = match self.author() {
+            Some(a) => a,
+            None => {
+                self.log.push("author is not selected".to_string());
+                return;
+            }
+        };
+        let address = match address_book.find(&author) {
+            Some(a) => a,
+            None => {
+                self.log.push("author is not in address book".to_string());
+                return;
+            }
+        };
+        let signable = match self.signable() {
+            Some(a) => a,
+            None => {
+                self.log.push("transaction is not complete".to_string());
+                return;
+            }
+        };
+        let signature = match (address.sign(&signable), address) {
+            (Some(a), _) => a,
+            (None, Address::Public(a)) => {
+                let public = *a;
+                let crypto = address_book.crypto(&author);
+                self.external_sign(crypto, public, &signable);
+                return;
+            }
+            (None, _) => {
+                self.log
+                    .push("author key is watch-only or locked".to_string());
+                return;
+            }
+        };
+        self.put_signature(signature);
+    }
+
+    /// Sign with own key, without falling back to external signer; for scripted use
+    pub fn sign_local(&mut self, address_book: &AddressBook) -> Result<(), String> {
+        let author = self.author().ok_or("author is not selected".to_string())?;
+        let address = address_book
+            .find(&author)
+            .ok_or("author is not in address book".to_string())?;
+        let signable = self
+            .signable()
+            .ok_or("transaction is not complete".to_string())?;
+        let signature = address
+            .sign(&signable)
+            .ok_or("author key is watch-only or locked".to_string())?;
+        self.put_signature(signature);
+        Ok(())
+    }
+
+    /// Set author to own account, for scripted use
+    pub fn set_author(&mut self, account_id: AccountId) -> Result<(), String> {
+        match account_id {
+            AccountId::Id32(a) => {
+                set_account_id(&mut self.transaction.author.content, a, self.metadata)
+                    .map_err(|e| e.to_string())
+            }
+            AccountId::Id20(a) => match find_array_u8_mut(&mut self.transaction.author.content, 20)
+            {
+                Some(b) => {
+                    *b = a.0.to_vec();
+                    Ok(())
+                }
+                None => Err("chain does not use 20-byte accounts".to_string()),
+            },
+        }
+    }
+
+    /// Set field of selected call from text, for scripted use
+    ///
+    /// Accounts are SS58, balances are in chain units, bytes are `0x`-prefixed hex or text,
+    /// enums without fields are variant names; anything else takes JSON of draft node.
+    pub fn set_arg(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let units = self.units.clone();
+        let metadata = self.metadata;
+        let fields = call_fields(&mut self.transaction.call.content).map_err(|e| e.to_string())?;
+        let field = fields
+            .iter_mut()
+            .find(|a| a.field_name.as_deref() == Some(name))
+            .ok_or(format!("no field {}", name))?;
+        let balance = is_balance_type_name(&field.type_name);
+        fill_from_text(
+            &mut field.type_to_fill.content,
+            value,
+            balance,
+            &units,
+            metadata,
+        )
+        .map_err(|e| format!("{}: {}", name, e))
+    }
+
+    /// Show payload for air-gapped signer as QR code, and write it to file
+    ///
+    /// Payload is marked with crypto of the key. Account id of ecdsa key is hash of its public
+    /// key, which external signer needs, so locked ecdsa keys are refused.
+    fn external_sign(&mut self, crypto: Crypto, public: H256, signable: &[u8]) {
+        if crypto == Crypto::Ecdsa {
+            self.log.push(
+                "ecdsa public key is not known from account id, unlock key to sign".to_string(),
+            );
+            return;
+        }
+        let (call, extensions) = match self.call_length(signable) {
+            Some(a) => signable.split_at(a),
+            None => {
+                self.log
+                    .push("could not find call boundary in signable payload".to_string());
+                return;
+            }
+        };
+        let payload = match uos::transaction(
+            crypto,
+            public.as_bytes(),
+            call,
+            extensions,
+            self.genesis_hash,
+        ) {
+            Ok(a) => a,
+            Err(e) => {
+                self.log.push(e.to_string());
+                return;
+            }
+        };
+        match fs::write(&self.payload_file, hex::encode(&payload)) {
+            Ok(()) => self
+                .log
+                .push(format!("payload written to {}", self.payload_file)),
+            Err(e) => self.log.push(format!("payload not written: {}", e)),
+        }
+        match uos::qr_text(&uos::frame(&payload)) {
+            Ok(a) => self.uos = Some((a, crypto)),
+            Err(e) => self.log.push(e.to_string()),
+        }
+    }
+
+    /// Put signature into signature slot, selecting matching `MultiSignature` variant
+    fn put_signature(&mut self, signature: Signature) {
+        let special = match signature {
+            Signature::Sr25519(a) => SpecialTypeToFill::SignatureSr25519(Some(SignatureSr25519(a))),
+            Signature::Ed25519(a) => SpecialTypeToFill::SignatureEd25519(Some(SignatureEd25519(a))),
+            Signature::Ecdsa(a) => SpecialTypeToFill::SignatureEcdsa(Some(SignatureEcdsa(a))),
+            // `EthereumSignature` is bare 65-byte array, not a variant
+            Signature::Ethereum(a) => {
+                match find_array_u8_mut(&mut self.transaction.signature.content, 65) {
+                    Some(b) => *b = a.to_vec(),
+                    None => self
+                        .log
+                        .push("chain does not accept Ethereum signatures".to_string()),
+                }
+                return;
+            }
+        };
+        let types = &self.metadata.types;
+        match self.transaction.signature.content {
+            TypeContentToFill::Variant(ref mut a) => {
+                if let Some(index) = a
+                    .available_variants
+                    .iter()
+                    .position(|v| v.name == signature.variant_name())
+                {
+                    if let Ok(b) = VariantSelector::new_at::<(), RuntimeMetadataV15>(
+                        &a.available_variants,
+                        &mut (),
+                        types,
+                        index,
+                    ) {
+                        *a = b
+                    }
+                }
+                if a.selected.name != signature.variant_name() {
+                    self.log.push(format!(
+                        "chain does not accept {} signatures",
+                        signature.variant_name()
+                    ));
+                    return;
+                }
+                if let Some(field) = a.selected.fields_to_fill.first_mut() {
+                    field.type_to_fill.content = TypeContentToFill::SpecialType(special);
+                }
+            }
+            ref mut a => *a = TypeContentToFill::SpecialType(special),
+        }
+    }
+
     pub fn signable(&mut self) -> Option<Vec<u8>> {
         self.transaction.sign_this()
     }
 
+    /// Length of encoded call at the start of signable payload
+    fn call_length(&self, signable: &[u8]) -> Option<usize> {
+        let mut position = 0;
+        decode_as_call_unmarked(&signable, &mut position, &mut (), self.metadata).ok()?;
+        Some(position)
+    }
+
+    /// Encoded call, if all its fields are filled
+    fn call_data(&self) -> Option<Vec<u8>> {
+        self.transaction.call.finalize().map(|a| a.encode())
+    }
+
+    /// Start wrapping current call into multisig call; chain data is needed to finish
+    ///
+    /// Author must be one of signatories.
+    pub fn start_multisig(&mut self, multisig: Multisig, approve_only: bool) -> Result<(), String> {
+        if self.is_waiting() {
+            return Err("previous wrapping is still waiting for chain".to_string());
+        }
+        let me = match self.author() {
+            Some(AccountId::Id32(a)) => a,
+            _ => return Err("select signatory as author first".to_string()),
+        };
+        if !multisig.signatories().contains(&me) {
+            return Err("author is not a signatory of multisig".to_string());
+        }
+        let call = self
+            .call_data()
+            .ok_or("complete the transaction first".to_string())?;
+        let call_hash = H256(blake2_256(&call));
+        self.log
+            .push(format!("call hash 0x{}", hex::encode(call_hash.0)));
+        self.multisig = Some(MultisigWrap {
+            multisig,
+            me,
+            approve_only,
+            call,
+            call_hash,
+            started: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Start wrapping current call into proxy call; delegations are read from chain to finish
+    ///
+    /// Author is the delegate.
+    pub fn start_proxy(&mut self, real: H256, proxy_type: Option<String>) -> Result<(), String> {
+        if self.is_waiting() {
+            return Err("previous wrapping is still waiting for chain".to_string());
+        }
+        let me = match self.author() {
+            Some(AccountId::Id32(a)) => a,
+            _ => return Err("select delegate as author first".to_string()),
+        };
+        if let Some(ref name) = proxy_type {
+            let known = self.proxy_types().map_err(|e| e.to_string())?;
+            if !known.iter().any(|(_, a)| a == name) {
+                return Err(format!(
+                    "unknown proxy type {}, chain has {}",
+                    name,
+                    known
+                        .iter()
+                        .map(|(_, a)| a.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ));
+            }
+        }
+        self.proxy = Some(ProxyWrap {
+            real,
+            me,
+            proxy_type,
+            started: Instant::now(),
+        });
+        Ok(())
+    }
+
+    fn is_waiting(&self) -> bool {
+        self.multisig.is_some() || self.proxy.is_some()
+    }
+
+    /// Wrapping takes call as it was when started, so edits wait for chain data
+    fn refuse_while_waiting(&mut self) -> bool {
+        let waiting = self.is_waiting();
+        if waiting {
+            self.log
+                .push("wrapping is still waiting for chain".to_string());
+        }
+        waiting
+    }
+
+    /// Give up wrapping that waits for chain, because chain refused request or did not answer
+    pub fn cancel_waiting(&mut self, reason: &str) {
+        if self.multisig.take().is_some() {
+            self.log.push(format!("multisig call not made: {}", reason));
+        }
+        if self.proxy.take().is_some() {
+            self.log.push(format!("proxy call not made: {}", reason));
+        }
+    }
+
+    /// Whether wrapping has waited for chain too long, and was given up
+    fn wait_expired(&mut self) -> bool {
+        let started = match (&self.multisig, &self.proxy) {
+            (Some(a), _) => a.started,
+            (_, Some(a)) => a.started,
+            (None, None) => return false,
+        };
+        if started.elapsed() < WAIT_TIMEOUT {
+            return false;
+        }
+        self.cancel_waiting(&format!(
+            "no answer from chain in {} s",
+            WAIT_TIMEOUT.as_secs()
+        ));
+        true
+    }
+
+    /// Variant indices and names of `ProxyType` of chain
+    fn proxy_types(&self) -> Result<Vec<(u8, String)>, crate::call_tree::Error> {
+        let mut call = self.transaction.call.content.clone();
+        let pallet_call = only_field(select_variant(&mut call, "Proxy", self.metadata)?)?;
+        let fields = select_variant(pallet_call, "proxy", self.metadata)?;
+        let proxy_type = only_field(set_option(
+            field(fields, "force_proxy_type")?,
+            true,
+            self.metadata,
+        )?)?;
+        match proxy_type {
+            TypeContentToFill::Variant(a) => Ok(a
+                .available_variants
+                .iter()
+                .map(|v| (v.index, v.name.clone()))
+                .collect()),
+            _ => Err(crate::call_tree::Error::UnexpectedType(
+                "proxy type enum".to_string(),
+            )),
+        }
+    }
+
+    /// Storage key of delegations of real account, while waiting
+    pub fn proxy_request(&mut self) -> Option<Vec<u8>> {
+        if self.wait_expired() {
+            return None;
+        }
+        self.proxy.as_ref().map(|a| proxy::storage_key(&a.real))
+    }
+
+    /// Wrap call into `Proxy.proxy`, if author has matching delegation from real account
+    pub fn finish_proxy(&mut self, proxies: Option<Vec<u8>>) {
+        let wrap = match self.proxy.take() {
+            Some(a) => a,
+            None => return,
+        };
+        let real_name = AccountId::Id32(wrap.real).name(self.ss58);
+        let proxies = match proxies.map(|a| Proxies::decode(&mut &a[..])) {
+            Some(Ok(a)) => a,
+            Some(Err(e)) => {
+                self.log.push(format!("could not read proxies: {}", e));
+                return;
+            }
+            None => {
+                self.log.push(format!("{} has no proxies", real_name));
+                return;
+            }
+        };
+        let types = match self.proxy_types() {
+            Ok(a) => a,
+            Err(e) => {
+                self.log.push(format!("proxy call not made: {}", e));
+                return;
+            }
+        };
+        let type_name = |index: u8| match types.iter().find(|(a, _)| *a == index) {
+            Some((_, name)) => name.to_string(),
+            None => format!("#{}", index),
+        };
+        let delegations = proxies.of_delegate(&wrap.me);
+        if delegations.is_empty() {
+            self.log
+                .push(format!("author is not a proxy of {}", real_name));
+            return;
+        }
+        self.log.push(format!(
+            "delegations of author from {}: {}",
+            real_name,
+            delegations
+                .iter()
+                .map(|a| format!("{} (delay {})", type_name(a.proxy_type), a.delay))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+        let delegation = match wrap.proxy_type {
+            Some(ref name) => delegations
+                .iter()
+                .find(|a| type_name(a.proxy_type) == *name),
+            None => delegations.iter().find(|a| a.delay == 0),
+        };
+        match delegation {
+            Some(a) if a.delay == 0 => {}
+            Some(a) => {
+                self.log.push(format!(
+                    "delegation has delay of {} blocks, call must be announced first",
+                    a.delay
+                ));
+                return;
+            }
+            None => {
+                self.log
+                    .push("no matching delegation without delay".to_string());
+                return;
+            }
+        }
+        let before = self.snapshot();
+        match wrap_proxy(
+            &mut self.transaction.call.content,
+            wrap.real,
+            wrap.proxy_type.as_deref(),
+            self.metadata,
+        ) {
+            Ok(()) => {
+                self.history.record(before);
+                self.set_position(0);
+                self.log
+                    .push(format!("call wrapped into proxy for {}", real_name));
+            }
+            Err(e) => self.log.push(format!("proxy call not made: {}", e)),
+        }
+    }
+
+    /// Storage key of pending multisig operation and call to estimate weight of, while waiting
+    pub fn multisig_request(&mut self) -> Option<(Vec<u8>, Vec<u8>)> {
+        if self.wait_expired() {
+            return None;
+        }
+        self.multisig
+            .as_ref()
+            .map(|a| (a.multisig.storage_key(&a.call_hash), a.call.to_vec()))
+    }
+
+    /// Wrap call into multisig call, with timepoint of pending operation and call weight
+    pub fn finish_multisig(&mut self, pending: Option<Vec<u8>>, weight: (u64, u64)) {
+        let wrap = match self.multisig.take() {
+            Some(a) => a,
+            None => return,
+        };
+        let pending = match pending.map(|a| Pending::decode(&mut &a[..])) {
+            Some(Ok(a)) => Some(a),
+            Some(Err(e)) => {
+                self.log
+                    .push(format!("could not read pending multisig: {}", e));
+                return;
+            }
+            None => None,
+        };
+        if let Some(ref a) = pending {
+            self.log.push(format!(
+                "pending since block {} extrinsic {}, approvals {}/{}",
+                a.when.height,
+                a.when.index,
+                a.approvals.len(),
+                wrap.multisig.threshold()
+            ));
+            if a.is_approved_by(&wrap.me) {
+                self.log
+                    .push("author has already approved this call".to_string());
+                return;
+            }
+        }
+        if self.call_data().map(|a| H256(blake2_256(&a))) != Some(wrap.call_hash) {
+            self.log
+                .push("call changed while waiting for chain, multisig call not made".to_string());
+            return;
+        }
+        let before = self.snapshot();
+        match wrap_multisig(
+            &mut self.transaction.call.content,
+            &wrap,
+            pending.map(|a| a.when),
+            weight,
+            self.metadata,
+        ) {
+            Ok(name) => {
+                self.history.record(before);
+                self.set_position(0);
+                self.log.push(format!(
+                    "call wrapped into {}, max weight {} / {}",
+                    name, weight.0, weight.1
+                ));
+            }
+            Err(e) => self.log.push(format!("multisig call not made: {}", e)),
+        }
+    }
+
     pub fn submittable_signed(&self) -> Option<Vec<u8>> {
         self.transaction
             .send_this_signed::<(), RuntimeMetadataV15>(self.metadata)
             .unwrap()
     }
 
+    /// `Pallet.call` names of batched calls; call taken out to edit is marked
+    pub fn batch_list(&self) -> Vec<String> {
+        let editing = self.batch_editing.as_ref().map(|(index, _)| *index);
+        self.batch
+            .iter()
+            .enumerate()
+            .map(|(index, call)| {
+                if Some(index) == editing {
+                    format!("{} (editing, add puts it back)", call_name(call))
+                } else {
+                    call_name(call)
+                }
+            })
+            .collect()
+    }
+
+    /// Move current call to the end of batch and start new call
+    ///
+    /// Call taken out to edit is put back in its place instead, and call that was current
+    /// before it is restored.
+    pub fn batch_add(&mut self) -> Result<(), String> {
+        if self.is_waiting() {
+            return Err("wrapping is still waiting for chain".to_string());
+        }
+        if let Some((index, call)) = self.batch_editing.clone() {
+            let before = self.snapshot();
+            self.history.record(before);
+            self.batch_editing = None;
+            self.batch[index] = std::mem::replace(&mut self.transaction.call.content, call);
+            self.set_position(0);
+            return Ok(());
+        }
+        let fresh = TransactionToFill::init(&mut (), self.metadata, self.genesis_hash)
+            .map_err(|e| format!("{:?}", e))?;
+        let before = self.snapshot();
+        self.history.record(before);
+        let call = std::mem::replace(&mut self.transaction.call, fresh.call);
+        self.batch.push(call.content);
+        self.set_position(0);
+        Ok(())
+    }
+
+    /// Drop batched call
+    pub fn batch_remove(&mut self, index: usize) -> Result<(), String> {
+        self.batch_index(index)?;
+        self.batch_not_editing()?;
+        let before = self.snapshot();
+        self.history.record(before);
+        self.batch.remove(index);
+        Ok(())
+    }
+
+    /// Swap batched call with the one before it, or after it
+    pub fn batch_move(&mut self, index: usize, up: bool) -> Result<(), String> {
+        self.batch_index(index)?;
+        self.batch_not_editing()?;
+        let other = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1).filter(|a| *a < self.batch.len())
+        }
+        .ok_or("call is already at the edge".to_string())?;
+        let before = self.snapshot();
+        self.history.record(before);
+        self.batch.swap(index, other);
+        Ok(())
+    }
+
+    /// Take batched call out to edit it in call tree; `batch_add` puts it back in its place
+    pub fn batch_edit(&mut self, index: usize) -> Result<(), String> {
+        self.batch_index(index)?;
+        self.batch_not_editing()?;
+        if self.is_waiting() {
+            return Err("wrapping is still waiting for chain".to_string());
+        }
+        let before = self.snapshot();
+        self.history.record(before);
+        let current = std::mem::replace(
+            &mut self.transaction.call.content,
+            self.batch[index].clone(),
+        );
+        self.batch_editing = Some((index, current));
+        self.set_position(0);
+        Ok(())
+    }
+
+    /// Replace current call with `Utility` call of all batched calls; batch is emptied
+    ///
+    /// `name` is `batch`, `batch_all` or `force_batch`.
+    pub fn batch_finish(&mut self, name: &str) -> Result<(), String> {
+        if self.is_waiting() {
+            return Err("wrapping is still waiting for chain".to_string());
+        }
+        if self.batch.is_empty() {
+            return Err("batch is empty, add calls first".to_string());
+        }
+        self.batch_not_editing()?;
+        let before = self.snapshot();
+        wrap_batch(
+            &mut self.transaction.call.content,
+            name,
+            &self.batch,
+            self.metadata,
+        )
+        .map_err(|e| format!("batch call not made: {}", e))?;
+        self.history.record(before);
+        self.log.push(format!(
+            "{} calls wrapped into Utility.{}",
+            self.batch.len(),
+            name
+        ));
+        self.batch.clear();
+        self.set_position(0);
+        Ok(())
+    }
+
+    /// Replace current call with `Utility.batch_all` of transfers from CSV file; gives total
+    /// and warnings on rows
+    ///
+    /// Transfers with memo are followed by `System.remark` of it.
+    pub fn bulk_transfers(&mut self, file: &str) -> Result<String, String> {
+        if self.is_waiting() {
+            return Err("wrapping is still waiting for chain".to_string());
+        }
+        let Transfers {
+            transfers,
+            total,
+            warnings,
+        } = read_transfers(
+            file,
+            &self.units,
+            self.ss58,
+            existential_deposit(self.metadata),
+        )
+        .map_err(|e| e.to_string())?;
+        let template = TransactionToFill::init(&mut (), self.metadata, self.genesis_hash)
+            .map_err(|e| format!("{:?}", e))?
+            .call
+            .content;
+        let mut calls = Vec::new();
+        for transfer in transfers.iter() {
+            calls.push(
+                transfer_call(&template, transfer, self.metadata)
+                    .map_err(|e| format!("transfer call not made: {}", e))?,
+            );
+            if let Some(ref memo) = transfer.memo {
+                calls.push(
+                    remark_call(&template, memo, self.metadata)
+                        .map_err(|e| format!("remark call not made: {}", e))?,
+                );
+            }
+        }
+        let before = self.snapshot();
+        wrap_batch(
+            &mut self.transaction.call.content,
+            "batch_all",
+            &calls,
+            self.metadata,
+        )
+        .map_err(|e| format!("batch call not made: {}", e))?;
+        self.history.record(before);
+        self.set_position(0);
+        let mut summary = format!(
+            "{} transfers, {} remarks, total {} ({} planck)",
+            transfers.len(),
+            calls.len() - transfers.len(),
+            self.units.format(total),
+            total
+        );
+        for (line, warning) in warnings {
+            summary.push_str(&format!("\r\nline {}: {}", line, warning));
+        }
+        Ok(summary)
+    }
+
+    fn batch_index(&self, index: usize) -> Result<(), String> {
+        if index < self.batch.len() {
+            Ok(())
+        } else {
+            Err(format!("no call {} in batch", index + 1))
+        }
+    }
+
+    fn batch_not_editing(&self) -> Result<(), String> {
+        match self.batch_editing {
+            Some((index, _)) => Err(format!(
+                "call {} is taken out to edit, add puts it back first",
+                index + 1
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Write part of transaction to file, or to clipboard without file; gives where it went
+    pub fn export(&mut self, kind: Kind, file: Option<&str>) -> Result<String, String> {
+        let data = match kind {
+            Kind::Call => self.call_data(),
+            Kind::Hash => self.call_data().map(|a| blake2_256(&a).to_vec()),
+            Kind::Signable => self.signable(),
+            Kind::Signed => self.submittable_signed(),
+        }
+        .ok_or(format!(
+            "{} is not ready, complete the transaction first",
+            kind.name()
+        ))?;
+        export::export(&data, file, &mut self.clipboard).map_err(|e| e.to_string())
+    }
+
     fn observable_field(&self) -> RefTypeToFill {
         let mut position = self.position;
         match peek(&self.transaction.author, position) {
@@ -361,6 +1484,114 @@ impl<'a, 'b> Builder<'a, 'b> {
         panic!("Transaction seems to be empty");
     }
 
+    pub fn draft_exists(&self, name: &str) -> bool {
+        Draft::exists(&self.drafts_dir, name)
+    }
+
+    /// Save transaction as it is now to named draft
+    pub fn save_draft(&self, name: &str, overwrite: bool) -> Result<(), String> {
+        Draft::new(&self.transaction, self.metadata, self.genesis_hash)
+            .save(&self.drafts_dir, name, overwrite)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Replace transaction with named draft; gives warnings for parts that did not fit
+    pub fn load_draft(&mut self, name: &str) -> Result<Vec<String>, String> {
+        if self.is_waiting() {
+            return Err("wrapping is still waiting for chain".to_string());
+        }
+        let draft = Draft::open(&self.drafts_dir, name).map_err(|e| e.to_string())?;
+        let mut transaction = TransactionToFill::init(&mut (), self.metadata, self.genesis_hash)
+            .map_err(|e| format!("{:?}", e))?;
+        let warnings = draft
+            .apply(&mut transaction, self.metadata, self.genesis_hash)
+            .map_err(|e| e.to_string())?;
+        let before = self.snapshot();
+        self.transaction = transaction;
+        self.history.record(before);
+        self.set_position(0);
+        self.selector = None;
+        self.details = false;
+        self.buffer = "".to_string();
+        self.uos = None;
+        Ok(warnings)
+    }
+
+    /// Select pallet and call in transaction, and move to the call card
+    ///
+    /// Fields are made anew if call changes; author and extensions are kept.
+    pub fn find_call(&mut self, entry: &CallEntry) -> Result<(), String> {
+        if self.is_waiting() {
+            return Err("wrapping is still waiting for chain".to_string());
+        }
+        // both names are resolved before transaction is touched
+        let mut content = self.transaction.call.content.clone();
+        let pallet = select_variant(&mut content, &entry.pallet, self.metadata)
+            .map_err(|e| e.to_string())?;
+        let calls = only_field(pallet).map_err(|e| e.to_string())?;
+        select_variant(calls, &entry.call, self.metadata).map_err(|e| e.to_string())?;
+        let before = self.snapshot();
+        self.transaction.call.content = content;
+        self.record_edit(before);
+        // pallet card, then call card
+        self.set_position(steamroller(&self.transaction.author, 0, &self.lookup(None)).len() + 1);
+        self.selector = None;
+        self.details = false;
+        self.buffer = "".to_string();
+        Ok(())
+    }
+
+    /// Replace transaction with call decoded from hex of call or extrinsic
+    ///
+    /// Author of signed extrinsic is kept; signature and extensions are not.
+    pub fn import_hex(&mut self, input: &str) -> Result<(Vec<String>, bool), String> {
+        if self.is_waiting() {
+            return Err("wrapping is still waiting for chain".to_string());
+        }
+        let imported = import(input, self.metadata).map_err(|e| e.to_string())?;
+        let mut transaction = TransactionToFill::init(&mut (), self.metadata, self.genesis_hash)
+            .map_err(|e| format!("{:?}", e))?;
+        let mut warnings = Vec::new();
+        if let Some(ref author) = imported.author {
+            draft::fill(
+                author,
+                &mut transaction.author.content,
+                self.metadata,
+                "author",
+                &mut warnings,
+            );
+        }
+        draft::fill(
+            &imported.call,
+            &mut transaction.call.content,
+            self.metadata,
+            "call",
+            &mut warnings,
+        );
+        let before = self.snapshot();
+        self.transaction = transaction;
+        self.history.record(before);
+        self.set_position(0);
+        self.selector = None;
+        self.details = false;
+        self.buffer = "".to_string();
+        self.uos = None;
+        Ok((warnings, imported.signed))
+    }
+
+    /// Extrinsic or call decoded from hex, with signature checked; transaction is not touched
+    pub fn decode(&self, input: &str, block: Option<&str>) -> Result<Value, String> {
+        report(
+            input,
+            block,
+            self.metadata,
+            self.genesis_hash,
+            &self.units,
+            self.ss58,
+        )
+        .map_err(|e| e.to_string())
+    }
+
     pub fn autofill(&mut self, block: H256, nonce: Option<u64>) {
         // TODO
         self.transaction.populate_block_hash(block);
@@ -380,15 +1611,279 @@ impl<'a, 'b> Builder<'a, 'b> {
     }
 }
 
+/// Put call into `Proxy.proxy`, with forced proxy type if it is set
+fn wrap_proxy(
+    content: &mut TypeContentToFill,
+    real: H256,
+    proxy_type: Option<&str>,
+    metadata: &RuntimeMetadataV15,
+) -> Result<(), crate::call_tree::Error> {
+    let inner = content.clone();
+    let mut outer = content.clone();
+    let pallet_call = only_field(select_variant(&mut outer, "Proxy", metadata)?)?;
+    let fields = select_variant(pallet_call, "proxy", metadata)?;
+    set_account_id(field(fields, "real")?, real, metadata)?;
+    let force_proxy_type = field(fields, "force_proxy_type")?;
+    match proxy_type {
+        Some(name) => {
+            let proxy_type = only_field(set_option(force_proxy_type, true, metadata)?)?;
+            select_variant(proxy_type, name, metadata)?;
+        }
+        None => {
+            set_option(force_proxy_type, false, metadata)?;
+        }
+    }
+    *field(fields, "call")? = inner;
+    *content = outer;
+    Ok(())
+}
+
+/// Make `Utility` call of given name with calls in it
+fn wrap_batch(
+    content: &mut TypeContentToFill,
+    name: &str,
+    calls: &[TypeContentToFill],
+    metadata: &RuntimeMetadataV15,
+) -> Result<(), crate::call_tree::Error> {
+    let mut outer = content.clone();
+    let pallet_call = only_field(select_variant(&mut outer, "Utility", metadata)?)?;
+    let fields = select_variant(pallet_call, name, metadata)?;
+    let elements = set_sequence_length(field(fields, "calls")?, calls.len(), metadata)?;
+    for (element, call) in elements.iter_mut().zip(calls) {
+        *element = call.clone();
+    }
+    *content = outer;
+    Ok(())
+}
+
+/// `Balances.transfer_keep_alive`, made from fresh call
+fn transfer_call(
+    template: &TypeContentToFill,
+    transfer: &Transfer,
+    metadata: &RuntimeMetadataV15,
+) -> Result<TypeContentToFill, crate::call_tree::Error> {
+    let mut content = template.clone();
+    let pallet_call = only_field(select_variant(&mut content, "Balances", metadata)?)?;
+    let fields = select_variant(pallet_call, "transfer_keep_alive", metadata)?;
+    set_account_id(field(fields, "dest")?, transfer.dest, metadata)?;
+    set_unsigned(field(fields, "value")?, transfer.amount)?;
+    Ok(content)
+}
+
+/// `System.remark`, made from fresh call
+fn remark_call(
+    template: &TypeContentToFill,
+    remark: &str,
+    metadata: &RuntimeMetadataV15,
+) -> Result<TypeContentToFill, crate::call_tree::Error> {
+    let mut content = template.clone();
+    let pallet_call = only_field(select_variant(&mut content, "System", metadata)?)?;
+    let fields = select_variant(pallet_call, "remark", metadata)?;
+    set_bytes(field(fields, "remark")?, remark.as_bytes())?;
+    Ok(content)
+}
+
+/// Fields of call selected in `RuntimeCall`
+fn call_fields(
+    content: &mut TypeContentToFill,
+) -> Result<&mut Vec<FieldToFill>, crate::call_tree::Error> {
+    let pallet = match content {
+        TypeContentToFill::Variant(a) => &mut a.selected.fields_to_fill,
+        _ => return Err(crate::call_tree::Error::UnexpectedType("call".to_string())),
+    };
+    match only_field(pallet)? {
+        TypeContentToFill::Variant(a) => Ok(&mut a.selected.fields_to_fill),
+        _ => Err(crate::call_tree::Error::UnexpectedType("call".to_string())),
+    }
+}
+
+/// Fill field from text, as typed in command line
+fn fill_from_text(
+    content: &mut TypeContentToFill,
+    value: &str,
+    balance: bool,
+    units: &Units,
+    metadata: &RuntimeMetadataV15,
+) -> Result<(), String> {
+    let value = value.trim();
+    if value.starts_with('{') || value.starts_with('"') {
+        let node: draft::Node = serde_json::from_str(value).map_err(|e| e.to_string())?;
+        let mut warnings = Vec::new();
+        draft::fill(&node, content, metadata, "value", &mut warnings);
+        return if warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(warnings.join("; "))
+        };
+    }
+    let balance = balance || balance_candidate(content).map(|(_, a)| a).unwrap_or(false);
+    if matches!(content, TypeContentToFill::Primitive(_)) && !balance {
+        check(content, value).map_err(|e| e.to_string())?;
+    }
+    let result = match content {
+        TypeContentToFill::ArrayU8(_) | TypeContentToFill::SequenceU8(_) => {
+            let bytes = match value.strip_prefix("0x") {
+                Some(a) => hex::decode(a).map_err(|e| e.to_string())?,
+                None => value.as_bytes().to_vec(),
+            };
+            set_bytes(content, &bytes)
+        }
+        TypeContentToFill::Primitive(
+            PrimitiveToFill::CompactUnsigned(_) | PrimitiveToFill::Unsigned(_),
+        ) => {
+            let number = if balance {
+                units.parse(value).map_err(|e| e.to_string())?
+            } else {
+                value.parse::<u128>().map_err(|e| e.to_string())?
+            };
+            set_unsigned(content, number)
+        }
+        TypeContentToFill::Primitive(PrimitiveToFill::Regular(_)) => set_from_str(content, value),
+        TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(_)) => set_account_id(
+            content,
+            account(value).map_err(|e| e.to_string())?,
+            metadata,
+        ),
+        // `MultiAddress` takes SS58 into `Id`; other enums take variant name
+        TypeContentToFill::Variant(_) => match account(value) {
+            Ok(a) => set_account_id(content, a, metadata),
+            Err(_) => select_variant(content, value, metadata).map(|_| ()),
+        },
+        _ => return Err("expected draft JSON for this field".to_string()),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// `Pallet.call` of `RuntimeCall`
+fn call_name(content: &TypeContentToFill) -> String {
+    let pallet = match content {
+        TypeContentToFill::Variant(a) => &a.selected,
+        _ => return "?".to_string(),
+    };
+    match pallet.fields_to_fill.as_slice() {
+        [a] => match &a.type_to_fill.content {
+            TypeContentToFill::Variant(b) => format!("{}.{}", pallet.name, b.selected.name),
+            _ => pallet.name.to_owned(),
+        },
+        _ => pallet.name.to_owned(),
+    }
+}
+
+/// Put call into `Multisig` pallet call; gives name of call used
+fn wrap_multisig(
+    content: &mut TypeContentToFill,
+    wrap: &MultisigWrap,
+    timepoint: Option<Timepoint>,
+    weight: (u64, u64),
+    metadata: &RuntimeMetadataV15,
+) -> Result<&'static str, crate::call_tree::Error> {
+    let inner = content.clone();
+    let mut outer = content.clone();
+    let threshold = wrap.multisig.threshold();
+    let name = if threshold == 1 {
+        "as_multi_threshold_1"
+    } else if wrap.approve_only {
+        "approve_as_multi"
+    } else {
+        "as_multi"
+    };
+    let pallet_call = only_field(select_variant(&mut outer, "Multisig", metadata)?)?;
+    let fields = select_variant(pallet_call, name, metadata)?;
+    set_account_ids(
+        field(fields, "other_signatories")?,
+        &wrap.multisig.other_signatories(&wrap.me),
+        metadata,
+    )?;
+    if threshold == 1 {
+        *field(fields, "call")? = inner;
+    } else {
+        set_unsigned(field(fields, "threshold")?, threshold as u128)?;
+        let maybe_timepoint = field(fields, "maybe_timepoint")?;
+        match timepoint {
+            Some(a) => {
+                let timepoint =
+                    composite_fields(only_field(set_option(maybe_timepoint, true, metadata)?)?)?;
+                set_unsigned(field(timepoint, "height")?, a.height as u128)?;
+                set_unsigned(field(timepoint, "index")?, a.index as u128)?;
+            }
+            None => {
+                set_option(maybe_timepoint, false, metadata)?;
+            }
+        }
+        if wrap.approve_only {
+            set_bytes(field(fields, "call_hash")?, wrap.call_hash.as_bytes())?;
+        } else {
+            *field(fields, "call")? = inner;
+        }
+        let max_weight = composite_fields(field(fields, "max_weight")?)?;
+        set_unsigned(field(max_weight, "ref_time")?, weight.0 as u128)?;
+        set_unsigned(field(max_weight, "proof_size")?, weight.1 as u128)?;
+    }
+    *content = outer;
+    Ok(name)
+}
+
+/// Find first byte array of given length within type, e.g. `AccountId20` inside of address
+fn find_array_u8(input: &TypeContentToFill, len: usize) -> Option<&[u8]> {
+    match input {
+        TypeContentToFill::ArrayU8(a) if a.len as usize == len && a.content.len() == len => {
+            Some(&a.content)
+        }
+        TypeContentToFill::Composite(a) => a
+            .iter()
+            .find_map(|i| find_array_u8(&i.type_to_fill.content, len)),
+        TypeContentToFill::Tuple(a) => a.iter().find_map(|i| find_array_u8(&i.content, len)),
+        TypeContentToFill::Variant(a) => a
+            .selected
+            .fields_to_fill
+            .iter()
+            .find_map(|i| find_array_u8(&i.type_to_fill.content, len)),
+        _ => None,
+    }
+}
+
+/// Same as [`find_array_u8`], but for modifications, and also picks unfilled arrays
+fn find_array_u8_mut(input: &mut TypeContentToFill, len: usize) -> Option<&mut Vec<u8>> {
+    match input {
+        TypeContentToFill::ArrayU8(a) if a.len as usize == len => Some(&mut a.content),
+        TypeContentToFill::Composite(a) => a
+            .iter_mut()
+            .find_map(|i| find_array_u8_mut(&mut i.type_to_fill.content, len)),
+        TypeContentToFill::Tuple(a) => a
+            .iter_mut()
+            .find_map(|i| find_array_u8_mut(&mut i.content, len)),
+        TypeContentToFill::Variant(a) => a
+            .selected
+            .fields_to_fill
+            .iter_mut()
+            .find_map(|i| find_array_u8_mut(&mut i.type_to_fill.content, len)),
+        _ => None,
+    }
+}
+
 /// Renderable card for single editable field
 pub struct Card {
     pub content: String,
     pub indent: usize,
+    /// Value is not set yet, transaction could not be made without it
+    pub missing: bool,
 }
 
 impl Card {
     pub fn new(content: String, indent: usize) -> Self {
-        Self { content, indent }
+        Self {
+            content,
+            indent,
+            missing: false,
+        }
+    }
+
+    pub fn missing(content: String, indent: usize) -> Self {
+        Self {
+            content,
+            indent,
+            missing: true,
+        }
     }
 }
 
@@ -396,6 +1891,7 @@ pub struct DetailsCard {
     pub content: String,
     pub info: String,
     pub buffer: Option<String>,
+    pub preview: Option<String>,
     pub selector: Option<Selector>,
 }
 
@@ -405,8 +1901,30 @@ impl DetailsCard {
         buffer: Option<String>,
         selector: Option<Selector>,
         address_book: &AddressBook,
+        units: &Units,
     ) -> Self {
         let info = input.info;
+        let preview = match (input.balance, &buffer) {
+            (true, Some(b)) if !b.is_empty() => Some(match units.parse(b) {
+                Ok(planck) => format!("= {} planck ({})", planck, units.format(planck)),
+                Err(e) => format!("Invalid: {}", e),
+            }),
+            (false, Some(b)) if selector.is_none() => check(input.content, b)
+                .err()
+                .map(|e| format!("Invalid: {}", e)),
+            (false, Some(_)) => match (&selector, input.content) {
+                (Some(s), TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(_)))
+                    if s.is_empty() =>
+                {
+                    Some(match account(s.filter()) {
+                        Ok(_) => "Valid address, Enter to use it".to_string(),
+                        Err(e) => format!("Invalid: {}", e),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        };
         /*
         .iter()
         .map(|a| a.docs.clone())
@@ -442,43 +1960,133 @@ impl DetailsCard {
                     a.selected.docs.replace("\n", "\r\n")
                 )
             }
-            inside @ _ => format!("{:?}", inside),
+            inside @ _ => match balance_candidate(inside) {
+                Some((value, _)) if input.balance => match unsigned_value(value) {
+                    Some(planck) => format!(
+                        "Balance: {}\r\n\r\n{} planck\r\n\r\nEnter amount in {} (e.g. 1.5 {}, 250m), or in planck (e.g. 1000 planck)",
+                        units.format(planck),
+                        planck,
+                        units.symbol(),
+                        units.symbol(),
+                    ),
+                    None => format!(
+                        "Balance not set\r\n\r\nEnter amount in {} (e.g. 1.5 {}, 250m), or in planck (e.g. 1000 planck)",
+                        units.symbol(),
+                        units.symbol(),
+                    ),
+                },
+                _ => format!("{:?}", inside),
+            },
         };
         DetailsCard {
             content,
             info,
             buffer,
+            preview,
             selector,
         }
     }
 }
 
+/// Own accounts and contacts to pick from; empty address book leaves field to typed input
+fn account_selector(address_book: &AddressBook) -> Option<Selector> {
+    let names = address_book.names();
+    if names.is_empty() {
+        None
+    } else {
+        Some(Selector::new(names, 0))
+    }
+}
+
+/// What is needed to show values for humans
+struct Lookup<'c> {
+    ss58: u16,
+    units: &'c Units,
+    address_book: Option<&'c AddressBook>,
+}
+
+impl<'c> Lookup<'c> {
+    /// Account name, with label if it is known
+    fn account(&self, account_id: AccountId) -> String {
+        labelled(
+            account_id.name(self.ss58),
+            self.address_book.and_then(|a| a.label_of(&account_id)),
+        )
+    }
+}
+
 /// Flatten whole type into renderable cards
 ///
 /// No depth counter implemented as this should be guaranteed elsewhere?
 /// in metadata or its parser?
 ///
 /// Either way, if this crashes, no biggie
-fn steamroller(input: &TypeToFill, indent: usize, ss58: u16) -> Vec<Card> {
-    steamroller_inside(&input.content, indent, ss58)
+fn steamroller(input: &TypeToFill, indent: usize, lookup: &Lookup) -> Vec<Card> {
+    steamroller_inside(&input.content, indent, lookup)
 }
 
-fn steamroller_inside(input: &TypeContentToFill, indent: usize, ss58: u16) -> Vec<Card> {
+/// Same as [`steamroller`], but field type name could mark primitive as balance
+fn steamroller_field(input: &FieldToFill, indent: usize, lookup: &Lookup) -> Vec<Card> {
+    if is_balance_type_name(&input.type_name) {
+        if let Some((value, _)) = balance_candidate(&input.type_to_fill.content) {
+            return vec![balance_card(value, indent, lookup.units)];
+        }
+    }
+    steamroller(&input.type_to_fill, indent, lookup)
+}
+
+fn balance_card(value: &UnsignedToFill, indent: usize, units: &Units) -> Card {
+    match unsigned_value(value) {
+        Some(planck) => Card::new(format!("Balance: {}", units.format(planck)), indent),
+        None => Card::missing(format!("Balance: ? {}", units.symbol()), indent),
+    }
+}
+
+fn steamroller_inside(input: &TypeContentToFill, indent: usize, lookup: &Lookup) -> Vec<Card> {
     let mut output = Vec::new();
+    if let Some((value, true)) = balance_candidate(input) {
+        output.push(balance_card(value, indent, lookup.units));
+        return output;
+    }
     match &input {
+        TypeContentToFill::ArrayU8(a) if a.len == 20 && a.content.len() == 20 => {
+            output.push(Card::new(
+                format!(
+                    "address: {}",
+                    lookup.account(AccountId::Id20(H160::from_slice(&a.content)))
+                ),
+                indent,
+            ));
+        }
+        TypeContentToFill::ArrayU8(a) if a.content.len() != a.len as usize => {
+            output.push(Card::missing(
+                format!(
+                    "0x{} ({} of {} bytes)",
+                    hex::encode(&a.content),
+                    a.content.len(),
+                    a.len
+                ),
+                indent,
+            ));
+        }
         TypeContentToFill::ArrayU8(a) => {
             output.push(Card::new(format!("0x{}", hex::encode(&a.content)), indent));
         }
         TypeContentToFill::ArrayRegular(a) => {
             for i in &a.content {
-                output.append(&mut steamroller_inside(&i, indent, ss58));
+                output.append(&mut steamroller_inside(&i, indent, lookup));
             }
         }
         TypeContentToFill::Composite(a) => {
             for i in a {
-                output.append(&mut steamroller(&i.type_to_fill, indent, ss58));
+                output.append(&mut steamroller_field(&i, indent, lookup));
             }
         }
+        TypeContentToFill::Primitive(
+            PrimitiveToFill::CompactUnsigned(a) | PrimitiveToFill::Unsigned(a),
+        ) if unsigned_value(&a.content).is_none() => {
+            output.push(Card::missing(format!("{:?}: not set", a.specialty), indent));
+        }
         TypeContentToFill::Primitive(PrimitiveToFill::CompactUnsigned(a)) => {
             output.push(Card::new(
                 format!("{:?}: {:?}", a.specialty, a.content),
@@ -503,14 +2111,17 @@ fn steamroller_inside(input: &TypeContentToFill, indent: usize, ss58: u16) -> Ve
                 indent,
             ));
             for i in &a.content {
-                output.append(&mut steamroller_inside(&i, indent + 1, ss58));
+                output.append(&mut steamroller_inside(&i, indent + 1, lookup));
             }
         }
         TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(None)) => {
-            output.push(Card::new("AccountId32".to_string(), indent));
+            output.push(Card::missing("AccountId32".to_string(), indent));
         }
         TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(Some(a))) => {
-            output.push(Card::new(format!("address: {}", a.as_base58(ss58)), indent));
+            output.push(Card::new(
+                format!("address: {}", lookup.account(AccountId::Id32(H256(a.0)))),
+                indent,
+            ));
             // TODO
         }
 
@@ -533,20 +2144,31 @@ fn steamroller_inside(input: &TypeContentToFill, indent: usize, ss58: u16) -> Ve
             ));
         }
         TypeContentToFill::SpecialType(SpecialTypeToFill::SignatureSr25519(None)) => {
-            output.push(Card::new(format!(">>>Sign here!<<<"), indent));
+            output.push(Card::missing(format!(">>>Sign here!<<<"), indent));
         }
         TypeContentToFill::SpecialType(SpecialTypeToFill::SignatureSr25519(Some(a))) => {
             output.push(Card::new(format!("Signed: {}", hex::encode(&a.0)), indent));
         }
+        TypeContentToFill::SpecialType(
+            SpecialTypeToFill::SignatureEd25519(None) | SpecialTypeToFill::SignatureEcdsa(None),
+        ) => {
+            output.push(Card::missing(format!(">>>Sign here!<<<"), indent));
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::SignatureEd25519(Some(a))) => {
+            output.push(Card::new(format!("Signed: {}", hex::encode(&a.0)), indent));
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::SignatureEcdsa(Some(a))) => {
+            output.push(Card::new(format!("Signed: {}", hex::encode(&a.0)), indent));
+        }
         TypeContentToFill::Tuple(a) => {
             for i in a {
-                output.append(&mut steamroller(&i, indent, ss58));
+                output.append(&mut steamroller(&i, indent, lookup));
             }
         }
         TypeContentToFill::Variant(a) => {
             output.push(Card::new(a.selected.name.clone(), indent));
             for i in &a.selected.fields_to_fill {
-                output.append(&mut steamroller(&i.type_to_fill, indent + 1, ss58));
+                output.append(&mut steamroller_field(&i, indent + 1, lookup));
             }
         }
         TypeContentToFill::VariantEmpty => {}
@@ -566,13 +2188,20 @@ enum Peeker<'a> {
 impl<'a> Peeker<'a> {
     fn done(content: &'a TypeContentToFill, info: &str) -> Self {
         let info = info.to_string();
-        Self::Done(RefTypeToFill { info, content })
+        let balance = matches!(balance_candidate(content), Some((_, true)));
+        Self::Done(RefTypeToFill {
+            info,
+            content,
+            balance,
+        })
     }
 }
 
 struct RefTypeToFill<'a> {
     info: String,
     content: &'a TypeContentToFill,
+    /// Primitive is known to hold balance, either from specialty or from field type name
+    balance: bool,
 }
 
 /// Extract type at given depth
@@ -589,6 +2218,19 @@ fn peek<'a>(input: &'a TypeToFill, position: usize) -> Peeker<'a> {
     )
 }
 
+/// Extract type at given depth, marking balance fields by their type name
+fn peek_field<'a>(input: &'a FieldToFill, position: usize) -> Peeker<'a> {
+    match peek(&input.type_to_fill, position) {
+        Peeker::Done(mut a) => {
+            if is_balance_type_name(&input.type_name) && balance_candidate(a.content).is_some() {
+                a.balance = true;
+            }
+            Peeker::Done(a)
+        }
+        a => a,
+    }
+}
+
 fn peek_inside<'a>(input: &'a TypeContentToFill, info: &str, position: usize) -> Peeker<'a> {
     let mut depth = position;
     match input {
@@ -602,7 +2244,7 @@ fn peek_inside<'a>(input: &'a TypeContentToFill, info: &str, position: usize) ->
         }
         TypeContentToFill::Composite(ref a) => {
             for i in a {
-                match peek(&i.type_to_fill, depth) {
+                match peek_field(i, depth) {
                     Peeker::Depth(a) => depth = a,
                     Peeker::Done(a) => return Peeker::Done(a),
                 }
@@ -634,7 +2276,7 @@ fn peek_inside<'a>(input: &'a TypeContentToFill, info: &str, position: usize) ->
             }
             depth -= 1;
             for i in &a.selected.fields_to_fill {
-                match peek(&i.type_to_fill, depth) {
+                match peek_field(i, depth) {
                     Peeker::Depth(a) => depth = a,
                     Peeker::Done(a) => return Peeker::Done(a),
                 }
@@ -660,13 +2302,20 @@ enum Diver<'a> {
 impl<'a> Diver<'a> {
     fn done(content: &'a mut TypeContentToFill, info: &str) -> Self {
         let info = info.to_string();
-        Self::Done(RefMutTypeToFill { info, content })
+        let balance = matches!(balance_candidate(content), Some((_, true)));
+        Self::Done(RefMutTypeToFill {
+            info,
+            content,
+            balance,
+        })
     }
 }
 
 struct RefMutTypeToFill<'a> {
     info: String,
     content: &'a mut TypeContentToFill,
+    /// Primitive is known to hold balance, either from specialty or from field type name
+    balance: bool,
 }
 
 /// Extract type at given depth
@@ -683,6 +2332,20 @@ fn dive<'a>(input: &'a mut TypeToFill, position: usize) -> Diver<'a> {
     )
 }
 
+/// Extract type at given depth, marking balance fields by their type name
+fn dive_field<'a>(input: &'a mut FieldToFill, position: usize) -> Diver<'a> {
+    let balance_name = is_balance_type_name(&input.type_name);
+    match dive(&mut input.type_to_fill, position) {
+        Diver::Done(mut a) => {
+            if balance_name && balance_candidate(a.content).is_some() {
+                a.balance = true;
+            }
+            Diver::Done(a)
+        }
+        a => a,
+    }
+}
+
 fn dive_inside<'a>(input: &'a mut TypeContentToFill, info: &str, position: usize) -> Diver<'a> {
     let mut depth = position;
 
@@ -720,7 +2383,7 @@ fn dive_inside<'a>(input: &'a mut TypeContentToFill, info: &str, position: usize
         }
         TypeContentToFill::Composite(ref mut a) => {
             for i in a {
-                match dive(&mut i.type_to_fill, depth) {
+                match dive_field(i, depth) {
                     Diver::Depth(a) => depth = a,
                     Diver::Done(a) => return Diver::Done(a),
                 }
@@ -746,7 +2409,7 @@ fn dive_inside<'a>(input: &'a mut TypeContentToFill, info: &str, position: usize
         TypeContentToFill::Variant(ref mut a) => {
             depth -= 1;
             for i in &mut a.selected.fields_to_fill {
-                match dive(&mut i.type_to_fill, depth) {
+                match dive_field(i, depth) {
                     Diver::Depth(a) => depth = a,
                     Diver::Done(a) => return Diver::Done(a),
                 }
@@ -767,3 +2430,378 @@ fn dive_hard<'a>(input: &'a mut TypeToFill, position: usize) -> RefMutTypeToFill
         _ => panic!("diver reached bottom of the pool!"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use frame_metadata::v15::{
+        CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletCallMetadata, PalletConstantMetadata,
+        PalletMetadata, SignedExtensionMetadata,
+    };
+    use scale_info::meta_type;
+
+    use crate::keystore::Keystore;
+
+    /// Just enough of a runtime for transaction to be made
+    #[allow(dead_code, non_camel_case_types)]
+    mod runtime {
+        use scale_info::TypeInfo;
+
+        pub type Balance = u128;
+
+        #[derive(TypeInfo)]
+        pub struct AccountId32([u8; 32]);
+
+        #[derive(TypeInfo)]
+        pub struct H256([u8; 32]);
+
+        #[derive(TypeInfo)]
+        pub enum MultiAddress {
+            Id(AccountId32),
+        }
+
+        #[derive(TypeInfo)]
+        pub struct RuntimeVersion {
+            pub spec_name: String,
+            pub spec_version: u32,
+            pub transaction_version: u32,
+        }
+
+        #[derive(TypeInfo)]
+        pub enum SystemCall {
+            remark { remark: Vec<u8> },
+        }
+
+        #[derive(TypeInfo)]
+        pub enum BalancesCall {
+            transfer_keep_alive {
+                dest: MultiAddress,
+                #[codec(compact)]
+                value: Balance,
+            },
+        }
+
+        #[derive(TypeInfo)]
+        pub enum UtilityCall {
+            batch { calls: Vec<RuntimeCall> },
+            batch_all { calls: Vec<RuntimeCall> },
+            force_batch { calls: Vec<RuntimeCall> },
+        }
+
+        #[derive(TypeInfo)]
+        pub enum RuntimeCall {
+            #[codec(index = 0)]
+            System(SystemCall),
+            #[codec(index = 5)]
+            Balances(BalancesCall),
+            #[codec(index = 40)]
+            Utility(UtilityCall),
+        }
+    }
+
+    fn pallet(name: &'static str, index: u8, calls: scale_info::MetaType) -> PalletMetadata {
+        PalletMetadata {
+            name,
+            storage: None,
+            calls: Some(PalletCallMetadata { ty: calls }),
+            event: None,
+            constants: Vec::new(),
+            error: None,
+            index,
+            docs: Vec::new(),
+        }
+    }
+
+    fn metadata() -> RuntimeMetadataV15 {
+        let mut system = pallet("System", 0, meta_type::<runtime::SystemCall>());
+        system.constants.push(PalletConstantMetadata {
+            name: "Version",
+            ty: meta_type::<runtime::RuntimeVersion>(),
+            value: ("test".to_string(), 1u32, 1u32).encode(),
+            docs: Vec::new(),
+        });
+        RuntimeMetadataV15::new(
+            vec![
+                system,
+                pallet("Balances", 5, meta_type::<runtime::BalancesCall>()),
+                pallet("Utility", 40, meta_type::<runtime::UtilityCall>()),
+            ],
+            ExtrinsicMetadata {
+                version: 4,
+                address_ty: meta_type::<runtime::MultiAddress>(),
+                call_ty: meta_type::<runtime::RuntimeCall>(),
+                signature_ty: meta_type::<[u8; 64]>(),
+                extra_ty: meta_type::<()>(),
+                signed_extensions: vec![
+                    SignedExtensionMetadata {
+                        identifier: "CheckSpecVersion",
+                        ty: meta_type::<()>(),
+                        additional_signed: meta_type::<u32>(),
+                    },
+                    SignedExtensionMetadata {
+                        identifier: "CheckGenesis",
+                        ty: meta_type::<()>(),
+                        additional_signed: meta_type::<runtime::H256>(),
+                    },
+                ],
+            },
+            meta_type::<()>(),
+            Vec::new(),
+            OuterEnums {
+                call_enum_ty: meta_type::<runtime::RuntimeCall>(),
+                event_enum_ty: meta_type::<()>(),
+                error_enum_ty: meta_type::<()>(),
+            },
+            CustomMetadata {
+                map: Default::default(),
+            },
+        )
+    }
+
+    fn builder(metadata: &RuntimeMetadataV15) -> Builder {
+        Builder::new(
+            metadata,
+            H256::zero(),
+            Map::new(),
+            "payload.txt".to_string(),
+            "drafts".to_string(),
+        )
+    }
+
+    fn entry(pallet: &str, call: &str) -> CallEntry {
+        CallEntry {
+            pallet: pallet.to_string(),
+            call: call.to_string(),
+            docs: String::new(),
+        }
+    }
+
+    fn current_call(builder: &Builder) -> String {
+        call_name(&builder.transaction.call.content)
+    }
+
+    /// Move to first card with balance
+    fn to_balance(builder: &mut Builder) {
+        let position = (0..builder.cards(None).len())
+            .find(|a| {
+                builder.position = *a;
+                builder.observable_field().balance
+            })
+            .expect("no balance card");
+        builder.set_position(position);
+    }
+
+    /// Open card, type text and confirm it
+    fn type_in(builder: &mut Builder, address_book: &AddressBook, text: &str) {
+        builder.enter(address_book);
+        builder.paste(text.to_string());
+        builder.enter(address_book);
+    }
+
+    #[test]
+    fn undo_and_redo_call_selection() {
+        let metadata = metadata();
+        let mut builder = builder(&metadata);
+        assert_eq!(current_call(&builder), "System.remark");
+        builder
+            .find_call(&entry("Balances", "transfer_keep_alive"))
+            .unwrap();
+        builder.find_call(&entry("Utility", "batch_all")).unwrap();
+
+        builder.undo();
+        assert_eq!(current_call(&builder), "Balances.transfer_keep_alive");
+        builder.undo();
+        assert_eq!(current_call(&builder), "System.remark");
+        builder.undo();
+        assert_eq!(builder.log.last().unwrap(), "nothing to undo");
+
+        builder.redo();
+        assert_eq!(current_call(&builder), "Balances.transfer_keep_alive");
+
+        // new edit drops undone states
+        builder.find_call(&entry("Utility", "batch")).unwrap();
+        assert!(builder.history.redo.is_empty());
+        builder.redo();
+        assert_eq!(builder.log.last().unwrap(), "nothing to redo");
+        assert_eq!(current_call(&builder), "Utility.batch");
+    }
+
+    #[test]
+    fn unknown_call_changes_nothing() {
+        let metadata = metadata();
+        let mut builder = builder(&metadata);
+        let before = builder.snapshot();
+        assert!(builder
+            .find_call(&entry("Balances", "transfer_typo"))
+            .is_err());
+        assert!(builder.find_call(&entry("Balancez", "remark")).is_err());
+        assert!(before.same_as(&builder.snapshot()));
+        assert!(builder.history.undo.is_empty());
+    }
+
+    #[test]
+    fn only_changes_are_recorded() {
+        let metadata = metadata();
+        let address_book = AddressBook::init(42, Keystore::default(), false).unwrap();
+        let mut builder = builder(&metadata);
+        builder
+            .find_call(&entry("Balances", "transfer_keep_alive"))
+            .unwrap();
+        to_balance(&mut builder);
+
+        type_in(&mut builder, &address_book, "not a number");
+        assert!(builder.log.last().unwrap().starts_with("balance not set"));
+        assert_eq!(builder.history.undo.len(), 1);
+
+        type_in(&mut builder, &address_book, "5");
+        assert_eq!(builder.history.undo.len(), 2);
+
+        // same value again changes nothing
+        type_in(&mut builder, &address_book, "5");
+        assert_eq!(builder.history.undo.len(), 2);
+
+        builder.undo();
+        builder.undo();
+        assert_eq!(current_call(&builder), "System.remark");
+    }
+
+    #[test]
+    fn history_is_limited() {
+        let metadata = metadata();
+        let mut builder = builder(&metadata);
+        let calls = [
+            entry("Balances", "transfer_keep_alive"),
+            entry("System", "remark"),
+        ];
+        for call in calls.iter().cycle().take(HISTORY_DEPTH + 1) {
+            builder.find_call(call).unwrap();
+        }
+        assert_eq!(builder.history.undo.len(), HISTORY_DEPTH);
+
+        // state before first edit is gone, state after it is the oldest one
+        for _ in 0..HISTORY_DEPTH {
+            builder.undo();
+        }
+        assert_eq!(current_call(&builder), "Balances.transfer_keep_alive");
+        builder.undo();
+        assert_eq!(builder.log.last().unwrap(), "nothing to undo");
+        assert_eq!(builder.history.redo.len(), HISTORY_DEPTH);
+    }
+
+    /// Builder with given calls in batch; current call is left fresh
+    fn batched<'a>(metadata: &'a RuntimeMetadataV15, calls: &[CallEntry]) -> Builder<'a> {
+        let mut builder = builder(metadata);
+        for call in calls {
+            builder.find_call(call).unwrap();
+            builder.batch_add().unwrap();
+        }
+        builder
+    }
+
+    #[test]
+    fn batch_add_remove_and_reorder() {
+        let metadata = metadata();
+        let mut builder = batched(
+            &metadata,
+            &[
+                entry("System", "remark"),
+                entry("Balances", "transfer_keep_alive"),
+                entry("Utility", "batch"),
+            ],
+        );
+        assert_eq!(
+            builder.batch_list(),
+            [
+                "System.remark",
+                "Balances.transfer_keep_alive",
+                "Utility.batch"
+            ]
+        );
+        assert_eq!(current_call(&builder), "System.remark");
+
+        builder.batch_move(2, true).unwrap();
+        builder.batch_move(0, false).unwrap();
+        assert_eq!(
+            builder.batch_list(),
+            [
+                "Utility.batch",
+                "System.remark",
+                "Balances.transfer_keep_alive"
+            ]
+        );
+        assert!(builder.batch_move(0, true).is_err());
+        assert!(builder.batch_move(2, false).is_err());
+
+        builder.batch_remove(1).unwrap();
+        assert_eq!(
+            builder.batch_list(),
+            ["Utility.batch", "Balances.transfer_keep_alive"]
+        );
+        assert!(builder.batch_remove(2).is_err());
+
+        builder.undo();
+        assert_eq!(builder.batch_list().len(), 3);
+    }
+
+    #[test]
+    fn batch_call_taken_out_and_put_back() {
+        let metadata = metadata();
+        let address_book = AddressBook::init(42, Keystore::default(), false).unwrap();
+        let mut builder = batched(
+            &metadata,
+            &[
+                entry("System", "remark"),
+                entry("Balances", "transfer_keep_alive"),
+            ],
+        );
+        builder.find_call(&entry("Utility", "force_batch")).unwrap();
+        let untouched = format!("{:?}", builder.batch[1]);
+
+        builder.batch_edit(1).unwrap();
+        assert_eq!(current_call(&builder), "Balances.transfer_keep_alive");
+        assert_eq!(
+            builder.batch_list()[1],
+            "Balances.transfer_keep_alive (editing, add puts it back)"
+        );
+        assert!(builder.batch_edit(0).is_err());
+        assert!(builder.batch_remove(0).is_err());
+        assert!(builder.batch_move(0, false).is_err());
+        assert!(builder.batch_finish("batch").is_err());
+
+        to_balance(&mut builder);
+        type_in(&mut builder, &address_book, "5");
+        builder.batch_add().unwrap();
+        assert_eq!(
+            builder.batch_list(),
+            ["System.remark", "Balances.transfer_keep_alive"]
+        );
+        assert_ne!(format!("{:?}", builder.batch[1]), untouched);
+        assert_eq!(current_call(&builder), "Utility.force_batch");
+    }
+
+    #[test]
+    fn batch_kinds() {
+        let metadata = metadata();
+        let remark = entry("System", "remark");
+        for (index, name) in ["batch", "batch_all", "force_batch"].iter().enumerate() {
+            let mut builder = batched(&metadata, &[remark.clone(), remark.clone()]);
+            builder.batch_finish(name).unwrap();
+            assert_eq!(current_call(&builder), format!("Utility.{}", name));
+            assert!(builder.batch.is_empty());
+            // `Utility` call with two empty remarks
+            assert_eq!(
+                builder.call_data().unwrap(),
+                [40, index as u8, 8, 0, 0, 0, 0, 0, 0]
+            );
+        }
+
+        let mut builder = batched(&metadata, &[remark]);
+        assert!(builder.batch_finish("batch_any").is_err());
+        assert_eq!(builder.batch_list(), ["System.remark"]);
+        assert_eq!(current_call(&builder), "System.remark");
+
+        let mut builder = batched(&metadata, &[]);
+        assert!(builder.batch_finish("batch").is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use frame_metadata::v15::RuntimeMetadataV15;
+
+use parity_scale_codec::{Compact, Decode};
+
+use scale_info::{form::PortableForm, Path, PortableRegistry, TypeDef, TypeDefPrimitive};
+
+use crate::draft::{Field, Node};
+
+#[derive(Debug)]
+pub enum Error {
+    Decode(String),
+    InvalidHex(String),
+    NoVariant(u8),
+    TrailingBytes(usize),
+    UnknownType(u32),
+    Unsupported(String),
+    UnsupportedVersion(u8),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Decode(a) => write!(f, "could not decode {}", a),
+            Error::InvalidHex(a) => write!(f, "invalid hex: {}", a),
+            Error::NoVariant(a) => write!(f, "no variant with index {}", a),
+            Error::TrailingBytes(a) => write!(f, "{} bytes left after decoding", a),
+            Error::UnknownType(a) => write!(f, "type {} is not in metadata", a),
+            Error::Unsupported(a) => write!(f, "{} could not be imported", a),
+            Error::UnsupportedVersion(a) => write!(f, "extrinsic version {} is not supported", a),
+        }
+    }
+}
+
+/// Call, with author if extrinsic was signed
+pub struct Imported {
+    pub author: Option<Node>,
+    pub call: Node,
+    /// Extrinsic had signature and extensions; these are dropped and must be made anew
+    pub signed: bool,
+}
+
+/// Decoded value, with its encoding
+pub struct Part {
+    pub node: Node,
+    pub bytes: Vec<u8>,
+}
+
+/// Signer, signature and extensions of signed extrinsic
+pub struct Signed {
+    pub address: Part,
+    pub signature: Part,
+    /// Extension identifiers with values, in metadata order
+    pub extensions: Vec<(String, Part)>,
+}
+
+/// Extrinsic or bare call
+pub struct Decoded {
+    /// Extrinsic version byte, none for bare call
+    pub version: Option<u8>,
+    pub signed: Option<Signed>,
+    pub call: Part,
+}
+
+/// Decode `0x`-prefixed or bare hex of call, or of whole extrinsic, signed or not
+///
+/// Input is taken as extrinsic if it starts with compact length of the rest, followed by
+/// version byte; otherwise it is a call.
+pub fn decode(input: &str, metadata: &RuntimeMetadataV15) -> Result<Decoded, Error> {
+    let bytes = hex::decode(input.trim().trim_start_matches("0x"))
+        .map_err(|e| Error::InvalidHex(e.to_string()))?;
+    let mut decoder = Decoder {
+        registry: &metadata.types,
+        input: &bytes,
+    };
+    let extrinsic = &metadata.extrinsic;
+    let decoded = match extrinsic_body(&bytes) {
+        Some(body) => {
+            decoder.input = &body[1..];
+            let signed = match body[0] {
+                0x04 => None,
+                0x84 => {
+                    let address = decoder.part(extrinsic.address_ty.id)?;
+                    let signature = decoder.part(extrinsic.signature_ty.id)?;
+                    let extensions = extrinsic
+                        .signed_extensions
+                        .iter()
+                        .map(|a| Ok((a.identifier.to_owned(), decoder.part(a.ty.id)?)))
+                        .collect::<Result<_, Error>>()?;
+                    Some(Signed {
+                        address,
+                        signature,
+                        extensions,
+                    })
+                }
+                a => return Err(Error::UnsupportedVersion(a)),
+            };
+            Decoded {
+                version: Some(body[0]),
+                signed,
+                call: decoder.part(extrinsic.call_ty.id)?,
+            }
+        }
+        None => Decoded {
+            version: None,
+            signed: None,
+            call: decoder.part(extrinsic.call_ty.id)?,
+        },
+    };
+    match decoder.input.len() {
+        0 => Ok(decoded),
+        a => Err(Error::TrailingBytes(a)),
+    }
+}
+
+/// Call, and author of signed extrinsic, to fill transaction with
+pub fn import(input: &str, metadata: &RuntimeMetadataV15) -> Result<Imported, Error> {
+    let decoded = decode(input, metadata)?;
+    Ok(Imported {
+        signed: decoded.signed.is_some(),
+        author: decoded.signed.map(|a| a.address.node),
+        call: decoded.call.node,
+    })
+}
+
+/// Extrinsic after length prefix, if the prefix matches and version is known
+fn extrinsic_body(bytes: &[u8]) -> Option<&[u8]> {
+    let mut input = bytes;
+    let length = Compact::<u32>::decode(&mut input).ok()?.0 as usize;
+    match input.first() {
+        Some(0x04 | 0x84) if input.len() == length => Some(input),
+        _ => None,
+    }
+}
+
+/// Decodes scale-encoded input into draft nodes, type by type
+struct Decoder<'a> {
+    registry: &'a PortableRegistry,
+    input: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn decode<T: Decode>(&mut self, name: &str) -> Result<T, Error> {
+        T::decode(&mut self.input).map_err(|e| Error::Decode(format!("{}: {}", name, e)))
+    }
+
+    fn take(&mut self, length: usize) -> Result<Vec<u8>, Error> {
+        if self.input.len() < length {
+            return Err(Error::Decode(format!("{} bytes", length)));
+        }
+        let (taken, rest) = self.input.split_at(length);
+        self.input = rest;
+        Ok(taken.to_vec())
+    }
+
+    fn part(&mut self, id: u32) -> Result<Part, Error> {
+        let before = self.input;
+        let node = self.node(id)?;
+        let bytes = before[..before.len() - self.input.len()].to_vec();
+        Ok(Part { node, bytes })
+    }
+
+    fn node(&mut self, id: u32) -> Result<Node, Error> {
+        let ty = self.registry.resolve(id).ok_or(Error::UnknownType(id))?;
+        if ty.path.segments.last().map(String::as_str) == Some("AccountId32") {
+            let account: [u8; 32] = self.decode("account")?;
+            return Ok(Node::Account(Some(hex::encode(account))));
+        }
+        match &ty.type_def {
+            TypeDef::Composite(a) => Ok(composite(&ty.path, self.fields(&a.fields)?)),
+            TypeDef::Variant(a) => {
+                let index: u8 = self.decode("variant index")?;
+                let variant = a
+                    .variants
+                    .iter()
+                    .find(|v| v.index == index)
+                    .ok_or(Error::NoVariant(index))?;
+                Ok(Node::Variant {
+                    name: variant.name.to_owned(),
+                    fields: self.fields(&variant.fields)?,
+                })
+            }
+            TypeDef::Sequence(a) => {
+                let length = self.decode::<Compact<u32>>("sequence length")?.0 as usize;
+                // even elements of zero size may not outnumber bytes left
+                if length > self.input.len() {
+                    return Err(Error::Decode(format!(
+                        "sequence of {} elements from {} bytes",
+                        length,
+                        self.input.len()
+                    )));
+                }
+                if self.is_u8(a.type_param.id) {
+                    Ok(Node::Bytes(hex::encode(self.take(length)?)))
+                } else {
+                    let elements = (0..length)
+                        .map(|_| self.node(a.type_param.id))
+                        .collect::<Result<_, _>>()?;
+                    Ok(Node::Sequence(elements))
+                }
+            }
+            TypeDef::Array(a) => {
+                if self.is_u8(a.type_param.id) {
+                    Ok(Node::Bytes(hex::encode(self.take(a.len as usize)?)))
+                } else {
+                    let elements = (0..a.len)
+                        .map(|_| self.node(a.type_param.id))
+                        .collect::<Result<_, _>>()?;
+                    Ok(Node::Array(elements))
+                }
+            }
+            TypeDef::Tuple(a) => {
+                let elements = a
+                    .fields
+                    .iter()
+                    .map(|b| self.node(b.id))
+                    .collect::<Result<_, _>>()?;
+                Ok(Node::Tuple(elements))
+            }
+            TypeDef::Primitive(a) => self.primitive(a),
+            TypeDef::Compact(a) => self.compact(a.type_param.id),
+            TypeDef::BitSequence(_) => Err(Error::Unsupported("bit sequence".to_string())),
+        }
+    }
+
+    /// Compact unsigned integer, or single-field composite wrapping one
+    fn compact(&mut self, id: u32) -> Result<Node, Error> {
+        let ty = self.registry.resolve(id).ok_or(Error::UnknownType(id))?;
+        let unsigned = |a: u128| Ok(Node::Unsigned(Some(a.to_string())));
+        match &ty.type_def {
+            TypeDef::Primitive(a) => match a {
+                TypeDefPrimitive::U8 => unsigned(self.decode::<Compact<u8>>("u8")?.0.into()),
+                TypeDefPrimitive::U16 => unsigned(self.decode::<Compact<u16>>("u16")?.0.into()),
+                TypeDefPrimitive::U32 => unsigned(self.decode::<Compact<u32>>("u32")?.0.into()),
+                TypeDefPrimitive::U64 => unsigned(self.decode::<Compact<u64>>("u64")?.0.into()),
+                TypeDefPrimitive::U128 => unsigned(self.decode::<Compact<u128>>("u128")?.0),
+                _ => Err(Error::Unsupported(format!("compact {:?}", a))),
+            },
+            TypeDef::Composite(a) if a.fields.len() == 1 => {
+                let field = Field {
+                    name: a.fields[0].name.to_owned(),
+                    node: self.compact(a.fields[0].ty.id)?,
+                };
+                Ok(composite(&ty.path, vec![field]))
+            }
+            _ => Err(Error::Unsupported(format!("compact type {}", id))),
+        }
+    }
+
+    fn fields(&mut self, fields: &[scale_info::Field<PortableForm>]) -> Result<Vec<Field>, Error> {
+        fields
+            .iter()
+            .map(|a| {
+                Ok(Field {
+                    name: a.name.to_owned(),
+                    node: self.node(a.ty.id)?,
+                })
+            })
+            .collect()
+    }
+
+    fn is_u8(&self, id: u32) -> bool {
+        matches!(
+            self.registry.resolve(id).map(|a| &a.type_def),
+            Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+        )
+    }
+
+    fn primitive(&mut self, primitive: &TypeDefPrimitive) -> Result<Node, Error> {
+        let unsigned = |a: u128| Ok(Node::Unsigned(Some(a.to_string())));
+        let text = |a: String| Ok(Node::Text(a));
+        match primitive {
+            TypeDefPrimitive::U8 => unsigned(self.decode::<u8>("u8")?.into()),
+            TypeDefPrimitive::U16 => unsigned(self.decode::<u16>("u16")?.into()),
+            TypeDefPrimitive::U32 => unsigned(self.decode::<u32>("u32")?.into()),
+            TypeDefPrimitive::U64 => unsigned(self.decode::<u64>("u64")?.into()),
+            TypeDefPrimitive::U128 => unsigned(self.decode::<u128>("u128")?),
+            TypeDefPrimitive::I8 => text(self.decode::<i8>("i8")?.to_string()),
+            TypeDefPrimitive::I16 => text(self.decode::<i16>("i16")?.to_string()),
+            TypeDefPrimitive::I32 => text(self.decode::<i32>("i32")?.to_string()),
+            TypeDefPrimitive::I64 => text(self.decode::<i64>("i64")?.to_string()),
+            TypeDefPrimitive::I128 => text(self.decode::<i128>("i128")?.to_string()),
+            TypeDefPrimitive::Bool => text(self.decode::<bool>("bool")?.to_string()),
+            TypeDefPrimitive::Char => {
+                let code: u32 = self.decode("char")?;
+                let c = char::from_u32(code).ok_or(Error::Decode("char".to_string()))?;
+                text(c.to_string())
+            }
+            TypeDefPrimitive::Str => text(self.decode::<String>("str")?),
+            TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+                self.take(32)?;
+                Ok(Node::Skipped)
+            }
+        }
+    }
+}
+
+/// Composite node; per-things are kept as text of their parts, as in drafts
+fn composite(path: &Path<PortableForm>, fields: Vec<Field>) -> Node {
+    let per_thing = matches!(
+        path.segments.last().map(String::as_str),
+        Some("PerU16" | "Perbill" | "Percent" | "Permill" | "Perquintill")
+    );
+    match &fields[..] {
+        [Field {
+            node: Node::Unsigned(Some(a)),
+            ..
+        }] if per_thing => Node::Text(a.to_owned()),
+        _ => Node::Composite(fields),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use parity_scale_codec::Encode;
+    use scale_info::{meta_type, Registry, TypeInfo};
+
+    #[derive(TypeInfo)]
+    struct Perbill(u32);
+
+    #[derive(TypeInfo)]
+    struct Wrapper(u64);
+
+    fn decoder_for<T: TypeInfo + 'static>(input: &[u8]) -> (PortableRegistry, u32, Vec<u8>) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&meta_type::<T>()).id;
+        (registry.into(), id, input.to_vec())
+    }
+
+    #[test]
+    fn compact_wrappers() {
+        let (registry, id, input) = decoder_for::<Compact<Wrapper>>(&Compact(5u64).encode());
+        let mut decoder = Decoder {
+            registry: &registry,
+            input: &input,
+        };
+        match decoder.node(id).unwrap() {
+            Node::Composite(a) => {
+                assert!(matches!(&a[..], [Field { node: Node::Unsigned(Some(b)), .. }] if b == "5"))
+            }
+            _ => panic!("expected composite"),
+        }
+        assert!(decoder.input.is_empty());
+
+        let (registry, id, input) = decoder_for::<Compact<Perbill>>(&Compact(500u32).encode());
+        let mut decoder = Decoder {
+            registry: &registry,
+            input: &input,
+        };
+        assert!(matches!(decoder.node(id).unwrap(), Node::Text(a) if a == "500"));
+
+        // u64::MAX does not fit into compact u32 of per-thing
+        let (registry, id, input) = decoder_for::<Compact<Perbill>>(&Compact(u64::MAX).encode());
+        let mut decoder = Decoder {
+            registry: &registry,
+            input: &input,
+        };
+        assert!(decoder.node(id).is_err());
+    }
+
+    #[test]
+    fn long_sequence_of_nothing() {
+        let (registry, id, input) = decoder_for::<Vec<()>>(&Compact(u32::MAX).encode());
+        let mut decoder = Decoder {
+            registry: &registry,
+            input: &input,
+        };
+        assert!(matches!(decoder.node(id), Err(Error::Decode(_))));
+    }
+}
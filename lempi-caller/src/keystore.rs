@@ -0,0 +1,279 @@
+use crypto_secretbox::{
+    aead::{Aead, KeyInit},
+    Key, Nonce, XSalsa20Poly1305,
+};
+
+use rand::{rngs::OsRng, RngCore};
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+use crate::author::Crypto;
+
+/// scrypt cost parameters for password-derived keys
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug)]
+pub enum Error {
+    DecryptionFailed,
+    EncryptionFailed,
+    File(String),
+    Format(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::DecryptionFailed => write!(f, "wrong password or damaged secret"),
+            Error::EncryptionFailed => write!(f, "could not encrypt secret"),
+            Error::File(a) => write!(f, "keystore file error: {}", a),
+            Error::Format(a) => write!(f, "keystore format error: {}", a),
+        }
+    }
+}
+
+/// Secret encrypted with password
+///
+/// Key is derived from password with scrypt, secret is sealed with xsalsa20-poly1305.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EncryptedSecret {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedSecret {
+    pub fn seal(secret: &str, password: &str) -> Result<Self, Error> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let cipher = XSalsa20Poly1305::new(&password_key(password, &salt)?);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|_| Error::EncryptionFailed)?;
+        Ok(Self {
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn open(&self, password: &str) -> Result<String, Error> {
+        let salt = hex::decode(&self.salt).map_err(|e| Error::Format(e.to_string()))?;
+        let nonce = hex::decode(&self.nonce).map_err(|e| Error::Format(e.to_string()))?;
+        if nonce.len() != NONCE_LEN {
+            return Err(Error::Format("nonce length".to_string()));
+        }
+        let ciphertext = hex::decode(&self.ciphertext).map_err(|e| Error::Format(e.to_string()))?;
+        let cipher = XSalsa20Poly1305::new(&password_key(password, &salt)?);
+        let secret = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| Error::DecryptionFailed)?;
+        String::from_utf8(secret).map_err(|_| Error::DecryptionFailed)
+    }
+}
+
+fn password_key(password: &str, salt: &[u8]) -> Result<Key, Error> {
+    let params = scrypt::Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P, 32)
+        .map_err(|e| Error::Format(e.to_string()))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| Error::Format(e.to_string()))?;
+    Ok(key.into())
+}
+
+/// Single keystore record
+///
+/// Watch-only records have no secret; secret is derivation string (SURI), encrypted.
+/// Public is account id, i.e. blake2 hash of public key for ecdsa.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Entry {
+    pub public: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub crypto: Crypto,
+    pub secret: Option<EncryptedSecret>,
+}
+
+/// Multisig definition; account id is derived from signatories and threshold
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MultisigEntry {
+    pub signatories: Vec<String>,
+    pub threshold: u16,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// On-disk storage of user accounts
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Keystore {
+    #[serde(skip)]
+    path: String,
+    pub entries: Vec<Entry>,
+    /// Recipients not controlled by user, watch-only
+    #[serde(default)]
+    pub contacts: Vec<Entry>,
+    #[serde(default)]
+    pub multisigs: Vec<MultisigEntry>,
+}
+
+impl Keystore {
+    /// Read keystore from file; missing file is an empty keystore, any other error is returned
+    pub fn open(path: &str) -> Result<Self, Error> {
+        let mut keystore = match File::open(path) {
+            Ok(mut file) => {
+                let mut content = String::new();
+                file.read_to_string(&mut content)
+                    .map_err(|e| Error::File(e.to_string()))?;
+                serde_json::from_str::<Keystore>(&content)
+                    .map_err(|e| Error::Format(e.to_string()))?
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Keystore::default(),
+            Err(e) => return Err(Error::File(e.to_string())),
+        };
+        keystore.path = path.to_string();
+        Ok(keystore)
+    }
+
+    /// Write keystore next to old one, readable by owner only, then replace old one with it
+    ///
+    /// Failed write leaves old keystore as it was.
+    pub fn save(&self) -> Result<(), Error> {
+        let content =
+            serde_json::to_string_pretty(&self).map_err(|e| Error::Format(e.to_string()))?;
+        let temporary = format!("{}.tmp", self.path);
+        // leftover of failed save could have other permissions
+        let _ = fs::remove_file(&temporary);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(&temporary)
+            .map_err(|e| Error::File(e.to_string()))?;
+        file.write_all(content.as_bytes())
+            .and_then(|_| file.sync_all())
+            .and_then(|_| fs::rename(&temporary, &self.path))
+            .map_err(|e| Error::File(e.to_string()))
+    }
+
+    /// Add record and save; record is dropped again if keystore could not be saved
+    pub fn push(&mut self, entry: Entry) -> Result<(), Error> {
+        self.entries.push(entry);
+        self.save().inspect_err(|_| {
+            self.entries.pop();
+        })
+    }
+
+    pub fn push_contact(&mut self, entry: Entry) -> Result<(), Error> {
+        self.contacts.push(entry);
+        self.save().inspect_err(|_| {
+            self.contacts.pop();
+        })
+    }
+
+    pub fn push_multisig(&mut self, entry: MultisigEntry) -> Result<(), Error> {
+        self.multisigs.push(entry);
+        self.save().inspect_err(|_| {
+            self.multisigs.pop();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keystore path in temporary directory, with no file there yet
+    fn path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "lempi-keystore-{}-{}.json",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_file(&path);
+        path.to_str().unwrap().to_string()
+    }
+
+    fn entry(public: &str) -> Entry {
+        Entry {
+            public: public.to_string(),
+            label: None,
+            crypto: Crypto::Sr25519,
+            secret: None,
+        }
+    }
+
+    #[test]
+    fn secret_round_trip() {
+        let sealed = EncryptedSecret::seal("bottom drive obey lake//Alice", "pass").unwrap();
+        assert_eq!(
+            sealed.open("pass").unwrap(),
+            "bottom drive obey lake//Alice"
+        );
+        assert!(matches!(sealed.open("wrong"), Err(Error::DecryptionFailed)));
+
+        // fresh salt and nonce each time
+        let again = EncryptedSecret::seal("bottom drive obey lake//Alice", "pass").unwrap();
+        assert_ne!(sealed.ciphertext, again.ciphertext);
+    }
+
+    #[test]
+    fn missing_file_is_empty_keystore() {
+        let keystore = Keystore::open(&path("missing")).unwrap();
+        assert!(keystore.entries.is_empty());
+        assert!(keystore.contacts.is_empty());
+        assert!(keystore.multisigs.is_empty());
+    }
+
+    #[test]
+    fn saved_for_owner_only() {
+        let path = path("saved");
+        let mut keystore = Keystore::open(&path).unwrap();
+        keystore.push(entry("aa")).unwrap();
+        keystore.push_contact(entry("bb")).unwrap();
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let reopened = Keystore::open(&path).unwrap();
+        assert_eq!(reopened.entries[0].public, "aa");
+        assert_eq!(reopened.contacts[0].public, "bb");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn failed_save_drops_record() {
+        let path = format!("{}/keystore.json", path("no-such-dir"));
+        let mut keystore = Keystore::open(&path).unwrap();
+        assert!(matches!(keystore.push(entry("aa")), Err(Error::File(_))));
+        assert!(keystore.entries.is_empty());
+        assert!(keystore.push_contact(entry("bb")).is_err());
+        assert!(keystore.contacts.is_empty());
+        let multisig = MultisigEntry {
+            signatories: vec!["aa".to_string(), "bb".to_string()],
+            threshold: 2,
+            label: None,
+        };
+        assert!(keystore.push_multisig(multisig).is_err());
+        assert!(keystore.multisigs.is_empty());
+    }
+}
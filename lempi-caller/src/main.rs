@@ -1,15 +1,15 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use std::time::Duration;
 
-use serde_json::Value;
+use serde_json::{json, Value};
 
 use substrate_constructor::fill_prepare::TransactionToFill;
 
 use termwiz::caps::Capabilities;
 use termwiz::cell::AttributeChange;
 use termwiz::color::AnsiColor;
-use termwiz::input::{InputEvent, KeyCode, KeyEvent};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers};
 use termwiz::surface::{Change, CursorVisibility, Position, Surface};
 use termwiz::terminal::buffered::BufferedTerminal;
 use termwiz::terminal::{new_terminal, Terminal};
@@ -22,18 +22,60 @@ mod chain;
 mod author;
 use author::AddressBook;
 
+mod bulk;
+
 mod call;
 use call::CallField;
 
+mod call_tree;
+
+mod crypto;
+
+mod decode;
+
 mod details;
 use details::Details;
 
+mod draft;
+
+mod export;
+
 mod extrinsic_builder;
 use extrinsic_builder::Builder;
 
+mod import;
+
+mod keystore;
+use keystore::Keystore;
+
+mod message;
+
+mod multisig;
+
+mod polkadot_js;
+
+mod prompt;
+use prompt::{Command, Prompt};
+
+mod proxy;
+
 mod scaffold;
 use scaffold::Scaffold;
 
+mod search;
+use search::CallEntry;
+
+mod sr25519;
+
+mod units;
+use units::Units;
+
+mod uos;
+
+mod validation;
+
+mod vrf;
+
 /// Command-line arguments
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -41,6 +83,73 @@ struct Args {
     /// Name of the person to greet
     #[arg(short, long, default_value_t = String::from("polkadot"))]
     chainspec: String,
+
+    /// Keystore file with user accounts
+    #[arg(short, long, default_value_t = String::from("../keystore.json"))]
+    keystore: String,
+
+    /// Add built-in development accounts (//Alice, //Kampela, ...)
+    #[arg(long)]
+    dev_accounts: bool,
+
+    /// File to write payloads for external signer to, in hex
+    #[arg(long, default_value_t = String::from("../payload.uos"))]
+    payload_file: String,
+
+    /// Directory to save transaction drafts to
+    #[arg(long, default_value_t = String::from("../drafts"))]
+    drafts: String,
+
+    #[command(subcommand)]
+    command: Option<Action>,
+}
+
+/// Actions without interactive interface
+#[derive(Subcommand, Debug)]
+enum Action {
+    /// Broadcast signed extrinsic exported earlier, and watch it until finalized
+    Submit {
+        /// File with extrinsic in hex
+        file: String,
+    },
+    /// Build call from arguments, sign it, and print result as JSON
+    Call(CallArgs),
+    /// Decode extrinsic, check its signature, and print result as JSON
+    Decode {
+        /// Extrinsic or call, in hex
+        hex: String,
+
+        /// Hash of block mortal extrinsic was made at; needed to check its signature
+        #[arg(long)]
+        block: Option<String>,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct CallArgs {
+    /// Pallet name, as in metadata
+    pallet: String,
+
+    /// Call name, as in metadata
+    call: String,
+
+    /// Call field as name=value; accounts in SS58, balances in chain units, bytes as 0x-hex or
+    /// text, enums as variant names, anything else as draft JSON
+    #[arg(long = "arg", value_name = "NAME=VALUE")]
+    args: Vec<String>,
+
+    /// Label of own account to sign with, or its address; keystore password is taken from
+    /// LEMPI_PASSWORD
+    #[arg(long)]
+    signer: String,
+
+    /// Broadcast signed extrinsic and wait until it is finalized
+    #[arg(long)]
+    submit: bool,
+
+    /// Write signed extrinsic to file, in hex
+    #[arg(long)]
+    export: Option<String>,
 }
 
 #[tokio::main]
@@ -49,6 +158,14 @@ async fn main() -> Result<(), Error> {
 
     let mut bc = chain::Blockchain::new(&format!("../chain-specs/{}.json", args.chainspec)).await;
 
+    if let Some(Action::Submit { ref file }) = args.command {
+        if let Err(e) = submit(&mut bc, file).await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // TODO: this should be a reference and builder should die and be reborn
     // if this changes
     let metadata = bc.metadata().clone();
@@ -64,9 +181,51 @@ async fn main() -> Result<(), Error> {
         42
     };
 
-    let address_book = AddressBook::init(ss58);
+    if let Some(Action::Decode { ref hex, ref block }) = args.command {
+        let units = Units::from_specs(&specs).unwrap_or_else(|_| Units::planck());
+        match decode::report(hex, block.as_deref(), &metadata, genesis_hash, &units, ss58) {
+            Ok(a) => println!("{}", serde_json::to_string_pretty(&a).unwrap()),
+            Err(e) => {
+                println!("{}", json!({ "error": e.to_string() }));
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    let address_book =
+        Keystore::open(&args.keystore).and_then(|a| AddressBook::init(ss58, a, args.dev_accounts));
+    let mut address_book = match address_book {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut builder = Builder::new(
+        &metadata,
+        genesis_hash,
+        specs,
+        args.payload_file,
+        args.drafts,
+    );
+    if let Some(Action::Call(call_args)) = args.command {
+        let result = match std::env::var("LEMPI_PASSWORD") {
+            Ok(password) if address_book.unlock(&password) == 0 => {
+                Err("no keys unlocked with LEMPI_PASSWORD".to_string())
+            }
+            _ => run_call(&mut bc, &address_book, &mut builder, call_args).await,
+        };
+        let (output, code) = call_output(result);
+        println!("{}", output);
+        if code != 0 {
+            std::process::exit(code);
+        }
+        return Ok(());
+    }
 
-    let mut builder = Builder::new(&metadata, &address_book, genesis_hash, specs);
+    let mut prompt: Option<Prompt> = None;
     let mut hash = bc.block();
 
     let caps = Capabilities::new_from_env()?;
@@ -101,14 +260,14 @@ async fn main() -> Result<(), Error> {
 
     let mut call_field = CallField::new(scaffold.call().surface());
     buf.draw_from_screen(
-        call_field.render(builder.call(), &builder.position()),
+        call_field.render(builder.call(&address_book), &builder.position()),
         scaffold.call().column(),
         scaffold.call().line(),
     );
 
     let mut details_field = Details::new(scaffold.details_panel().surface());
     buf.draw_from_screen(
-        details_field.render(builder.details()),
+        details_field.render(builder.details(&address_book)),
         scaffold.details_panel().column(),
         scaffold.details_panel().line(),
     );
@@ -117,6 +276,7 @@ async fn main() -> Result<(), Error> {
 
     loop {
         log_field.add_change(builder.log());
+        log_field.add_change(address_book.log());
         log_field.add_change(bc.log());
         buf.draw_from_screen(
             &log_field,
@@ -132,19 +292,37 @@ async fn main() -> Result<(), Error> {
 
         let updated = bc.crank();
         let nonce = if let Some(a) = builder.author() {
-            bc.nonce(a, builder.ss58)
+            bc.nonce(a.name(builder.ss58))
         } else {
             None
         };
+        if let Some((key, call)) = builder.multisig_request() {
+            match (bc.storage(&key), bc.call_weight(&call)) {
+                (Some(Err(e)), _) | (_, Some(Err(e))) => builder.cancel_waiting(&e),
+                (Some(Ok(pending)), Some(Ok(weight))) => builder.finish_multisig(pending, weight),
+                _ => {}
+            }
+        }
+        if let Some(key) = builder.proxy_request() {
+            match bc.storage(&key) {
+                Some(Ok(proxies)) => builder.finish_proxy(proxies),
+                Some(Err(e)) => builder.cancel_waiting(&e),
+                None => {}
+            }
+        }
         if updated {
             builder.autofill(hash, nonce);
             buf.draw_from_screen(
-                call_field.render(builder.call(), &builder.position()),
+                call_field.render(builder.call(&address_book), &builder.position()),
                 scaffold.call().column(),
                 scaffold.call().line(),
             );
+            let details = match prompt {
+                Some(ref a) => a.details(ss58),
+                None => builder.details(&address_book),
+            };
             buf.draw_from_screen(
-                details_field.render(builder.details()),
+                details_field.render(details),
                 scaffold.details_panel().column(),
                 scaffold.details_panel().line(),
             );
@@ -158,6 +336,71 @@ async fn main() -> Result<(), Error> {
 
         match buf.terminal().poll_input(Some(Duration::new(1, 0))) {
             Ok(Some(input)) => match input {
+                // Prompt takes over all input while open
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    ..
+                }) if prompt.is_some() => {
+                    prompt = None;
+                    buf.draw_from_screen(
+                        details_field.render(builder.details(&address_book)),
+                        scaffold.details_panel().column(),
+                        scaffold.details_panel().line(),
+                    );
+                }
+                InputEvent::Paste(s) if prompt.is_some() => {
+                    if let Some(ref mut a) = prompt {
+                        a.paste(s)
+                    }
+                }
+                InputEvent::Key(key) if prompt.is_some() => {
+                    match key {
+                        KeyEvent {
+                            key: KeyCode::Enter,
+                            ..
+                        } => {
+                            if let Some(a) = prompt.take() {
+                                match a.submit(&mut address_book, &mut builder) {
+                                    Ok(next) => prompt = next,
+                                    Err(e) => {
+                                        log_field.add_change(format!("prompt: {}\r\n", e));
+                                    }
+                                }
+                            }
+                        }
+                        KeyEvent {
+                            key: KeyCode::Backspace,
+                            ..
+                        } => {
+                            if let Some(ref mut a) = prompt {
+                                a.backspace()
+                            }
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char(c),
+                            ..
+                        } => {
+                            if let Some(ref mut a) = prompt {
+                                a.input(c)
+                            }
+                        }
+                        _ => {}
+                    };
+                    let details = match prompt {
+                        Some(ref a) => a.details(ss58),
+                        None => builder.details(&address_book),
+                    };
+                    buf.draw_from_screen(
+                        call_field.render(builder.call(&address_book), &builder.position()),
+                        scaffold.call().column(),
+                        scaffold.call().line(),
+                    );
+                    buf.draw_from_screen(
+                        details_field.render(details),
+                        scaffold.details_panel().column(),
+                        scaffold.details_panel().line(),
+                    );
+                }
                 // Important global buttons that do not care what is selected
                 InputEvent::Key(KeyEvent {
                     key: KeyCode::Escape,
@@ -199,7 +442,7 @@ async fn main() -> Result<(), Error> {
                             key: KeyCode::Enter,
                             ..
                         } => {
-                            builder.enter();
+                            builder.enter(&address_book);
                         }
                         KeyEvent {
                             key: KeyCode::Backspace,
@@ -209,11 +452,233 @@ async fn main() -> Result<(), Error> {
                         }
                         KeyEvent {
                             key: KeyCode::Tab, ..
+                        } => match builder.submittable_signed() {
+                            Some(a) => bc.send(&a),
+                            None => {
+                                log_field.add_change(
+                                    "builder: transaction is incomplete, fill fields marked with !\r\n",
+                                );
+                            }
+                        },
+                        KeyEvent {
+                            key: KeyCode::Function(2),
+                            ..
                         } => {
-                            if let Some(a) = builder.submittable_signed() {
-                                bc.send(&a);
+                            if address_book.is_unlocked() {
+                                address_book.lock();
+                            } else {
+                                prompt =
+                                    Some(Prompt::new(Command::Unlock, "Keystore password:", true));
                             }
                         }
+                        KeyEvent {
+                            key: KeyCode::Function(3),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::Mnemonic,
+                                "Mnemonic phrase to import, or empty (or 24) to generate new one:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(4),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::WatchOnly(false),
+                                "SS58 address to watch:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(5),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::WatchOnly(true),
+                                "SS58 address of contact:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(6),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::ImportJson,
+                                "Path to polkadot-js JSON backup:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(7),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::ExportJson,
+                                "Address of unlocked sr25519 or ed25519 account to back up:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(8),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::MultisigSignatories,
+                                "Multisig signatories, SS58 addresses separated by spaces:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(9),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::MultisigWrap,
+                                "Multisig address to send the call from:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(10),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::ProxyReal,
+                                "Real account to act for as proxy:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(11),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::SignAccount,
+                                "Address of unlocked account to sign message with:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Function(12),
+                            ..
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::VerifyAccount,
+                                "Address of message signer, SS58 or 0x-prefixed 20-byte hex:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('d'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::DeriveParent,
+                                "Own watch-only address to derive from:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('s'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::SaveDraft,
+                                "Draft name to save transaction as:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('o'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::LoadDraft,
+                                "Draft name to load; current transaction is replaced:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('e'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::Export,
+                                "Export call, hash, signable or signed, followed by file name; without file it is copied to clipboard:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('b'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::batch(&builder));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('t'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::BulkTransfers,
+                                "CSV file of transfers, address,amount[,memo] per row; current call is replaced:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('z'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            builder.undo();
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('y'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            builder.redo();
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('f'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::FindCall(search::calls(&metadata)),
+                                "Find call by pallet.call name or docs; Enter jumps to best match:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('p'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::ImportHex,
+                                "Hex of call or extrinsic to import; current transaction is replaced:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('x'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::Decode,
+                                "Hex of extrinsic to decode and verify, and hash of its block if it is mortal:",
+                                false,
+                            ));
+                        }
+                        KeyEvent {
+                            key: KeyCode::Char('r'),
+                            modifiers: Modifiers::CTRL,
+                        } => {
+                            prompt = Some(Prompt::new(
+                                Command::VrfAccount,
+                                "Own unlocked sr25519 address to make VRF signature, or any address to verify:",
+                                false,
+                            ));
+                        }
                         KeyEvent {
                             key: KeyCode::Char(c),
                             ..
@@ -224,12 +689,16 @@ async fn main() -> Result<(), Error> {
                         _ => {}
                     };
                     buf.draw_from_screen(
-                        call_field.render(builder.call(), &builder.position()),
+                        call_field.render(builder.call(&address_book), &builder.position()),
                         scaffold.call().column(),
                         scaffold.call().line(),
                     );
+                    let details = match prompt {
+                        Some(ref a) => a.details(ss58),
+                        None => builder.details(&address_book),
+                    };
                     buf.draw_from_screen(
-                        details_field.render(builder.details()),
+                        details_field.render(details),
                         scaffold.details_panel().column(),
                         scaffold.details_panel().line(),
                     );
@@ -246,3 +715,171 @@ async fn main() -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Send extrinsic from file and print chain log until it is finalized or dropped
+async fn submit(bc: &mut chain::Blockchain, file: &str) -> Result<(), String> {
+    let extrinsic = export::read(file).map_err(|e| e.to_string())?;
+    bc.send(&extrinsic);
+    watch_submission(bc, true).await.map(|_| ())
+}
+
+/// Time to wait for submitted extrinsic to be finalized or dropped
+const SUBMISSION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Wait until submitted extrinsic is finalized; its final status, or why it is not finalized
+async fn watch_submission(bc: &mut chain::Blockchain, print_log: bool) -> Result<Value, String> {
+    let started = std::time::Instant::now();
+    loop {
+        bc.crank();
+        if print_log {
+            print!("{}", bc.log());
+        }
+        if bc.submission_finished() {
+            break;
+        }
+        if started.elapsed() > SUBMISSION_TIMEOUT {
+            return Err(format!(
+                "extrinsic not finalized in {} s",
+                SUBMISSION_TIMEOUT.as_secs()
+            ));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let status = bc.submission().cloned().unwrap_or_default();
+    if bc.submission_failed() {
+        return Err(format!("extrinsic not finalized: {}", status));
+    }
+    Ok(status)
+}
+
+/// Time to wait for nonce of signer
+const NONCE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// `--arg` split into field name and value; value itself may contain `=`
+fn field_arg(arg: &str) -> Result<(&str, &str), String> {
+    arg.split_once('=')
+        .map(|(name, value)| (name.trim(), value))
+        .ok_or(format!("argument {} is not name=value", arg))
+}
+
+/// JSON printed by `call` subcommand, and exit code
+fn call_output(result: Result<Value, String>) -> (Value, i32) {
+    match result {
+        Ok(a) => (a, 0),
+        Err(e) => (json!({ "error": e }), 1),
+    }
+}
+
+/// Fill, sign, and export or submit call, as asked in command line
+async fn run_call(
+    bc: &mut chain::Blockchain,
+    address_book: &AddressBook,
+    builder: &mut Builder<'_>,
+    call_args: CallArgs,
+) -> Result<Value, String> {
+    builder.find_call(&CallEntry {
+        pallet: call_args.pallet,
+        call: call_args.call,
+        docs: String::new(),
+    })?;
+    for arg in call_args.args.iter() {
+        let (name, value) = field_arg(arg)?;
+        builder.set_arg(name, value)?;
+    }
+    let author = address_book
+        .find_author(&call_args.signer)
+        .ok_or(format!("no own account {}", call_args.signer))?;
+    builder.set_author(author)?;
+
+    let started = std::time::Instant::now();
+    let nonce = loop {
+        bc.crank();
+        if let Some(a) = bc.nonce(author.name(builder.ss58)) {
+            break a;
+        }
+        if started.elapsed() > NONCE_TIMEOUT {
+            return Err("chain did not give nonce of signer".to_string());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    builder.autofill(bc.block(), Some(nonce));
+    builder.sign_local(address_book)?;
+
+    let extrinsic = builder
+        .submittable_signed()
+        .ok_or("transaction is incomplete".to_string())?;
+    let mut result = json!({
+        "signer": author.name(builder.ss58),
+        "nonce": nonce,
+        "block_hash": format!("0x{}", hex::encode(bc.block())),
+        "extrinsic": format!("0x{}", hex::encode(&extrinsic)),
+    });
+    if let Some(ref file) = call_args.export {
+        export::export(&extrinsic, Some(file), &mut export::Clipboard::default())
+            .map_err(|e| e.to_string())?;
+        result["exported"] = json!(file);
+    }
+    if call_args.submit {
+        bc.send(&extrinsic);
+        result["status"] = watch_submission(bc, false).await?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::error::ErrorKind;
+
+    #[test]
+    fn call_arguments() {
+        let args = Args::try_parse_from([
+            "lempi",
+            "call",
+            "Balances",
+            "transfer_keep_alive",
+            "--arg",
+            "dest=5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "--arg",
+            "value=1.5",
+            "--signer",
+            "alice",
+            "--export",
+            "signed.hex",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Action::Call(a)) => {
+                assert_eq!(a.pallet, "Balances");
+                assert_eq!(a.call, "transfer_keep_alive");
+                assert_eq!(a.args.len(), 2);
+                assert_eq!(a.signer, "alice");
+                assert!(!a.submit);
+                assert_eq!(a.export.as_deref(), Some("signed.hex"));
+            }
+            other => panic!("expected call, got {:?}", other),
+        }
+
+        let missing = Args::try_parse_from(["lempi", "call", "System", "remark"]).unwrap_err();
+        assert_eq!(missing.kind(), ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    fn field_arguments() {
+        assert_eq!(field_arg(" remark =a=b").unwrap(), ("remark", "a=b"));
+        assert_eq!(field_arg("value=").unwrap(), ("value", ""));
+        assert!(field_arg("value").is_err());
+    }
+
+    #[test]
+    fn call_errors_exit() {
+        let (output, code) = call_output(Err("no own account bob".to_string()));
+        assert_eq!(output, json!({ "error": "no own account bob" }));
+        assert_eq!(code, 1);
+
+        let (output, code) = call_output(Ok(json!({ "nonce": 3 })));
+        assert_eq!(output["nonce"], 3);
+        assert_eq!(code, 0);
+    }
+}
@@ -0,0 +1,199 @@
+use primitive_types::{H160, H256};
+
+use sp_core::{blake2_256, ecdsa, ed25519, keccak_256, Pair};
+
+use crate::author::{ethereum_address, AccountId, Crypto};
+use crate::crypto::Pair as _;
+use crate::sr25519;
+
+const BYTES_PREFIX: &[u8] = b"<Bytes>";
+const BYTES_POSTFIX: &[u8] = b"</Bytes>";
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidHex(String),
+    InvalidSignature(usize),
+    NotVerified,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::InvalidHex(a) => write!(f, "invalid hex: {}", a),
+            Error::InvalidSignature(a) => write!(f, "signature of unexpected length {}", a),
+            Error::NotVerified => write!(f, "signature does not match address and message"),
+        }
+    }
+}
+
+/// Message as typed: `0x`-prefixed hex is bytes, anything else is text
+pub fn parse_message(input: &str) -> Result<Vec<u8>, Error> {
+    match input.strip_prefix("0x") {
+        Some(a) => hex::decode(a).map_err(|e| Error::InvalidHex(e.to_string())),
+        None => Ok(input.as_bytes().to_vec()),
+    }
+}
+
+/// Wrap message in `<Bytes>...</Bytes>`, as wallets do so that message could not be transaction
+pub fn wrap_bytes(message: &[u8]) -> Vec<u8> {
+    if message.starts_with(BYTES_PREFIX) && message.ends_with(BYTES_POSTFIX) {
+        message.to_vec()
+    } else {
+        [BYTES_PREFIX, message, BYTES_POSTFIX].concat()
+    }
+}
+
+/// Check signature of account over message, wrapped or not
+///
+/// Gives crypto of signature and whether it was made over wrapped message.
+pub fn verify(
+    account_id: &AccountId,
+    message: &[u8],
+    signature: &str,
+) -> Result<(Crypto, bool), Error> {
+    let signature = hex::decode(signature.trim().trim_start_matches("0x"))
+        .map_err(|e| Error::InvalidHex(e.to_string()))?;
+    let wrapped = wrap_bytes(message);
+    for (candidate, is_wrapped) in [(&wrapped[..], true), (message, false)] {
+        let crypto = match account_id {
+            AccountId::Id32(a) => verify_exact(a, candidate, &signature)?,
+            AccountId::Id20(a) => {
+                verify_ethereum(a, candidate, &signature)?.then_some(Crypto::Ethereum)
+            }
+        };
+        if let Some(crypto) = crypto {
+            return Ok((crypto, is_wrapped));
+        }
+    }
+    Err(Error::NotVerified)
+}
+
+/// Check signature over message as it is; `None` if no crypto matches
+pub fn verify_exact(
+    account_id: &H256,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<Option<Crypto>, Error> {
+    match signature.len() {
+        64 => {
+            let raw: [u8; 64] = signature.try_into().expect("length checked; qed");
+            if sr25519::Pair::verify(
+                &sr25519::Signature::from_raw(raw),
+                message,
+                &sr25519::Public::from_raw(account_id.0),
+            ) {
+                Ok(Some(Crypto::Sr25519))
+            } else if ed25519::Pair::verify(
+                &ed25519::Signature::from_raw(raw),
+                message,
+                &ed25519::Public::from_raw(account_id.0),
+            ) {
+                Ok(Some(Crypto::Ed25519))
+            } else {
+                Ok(None)
+            }
+        }
+        // ecdsa account id is hash of public key, so public key is recovered
+        65 => {
+            let raw: [u8; 65] = signature.try_into().expect("length checked; qed");
+            match ecdsa::Signature::from_raw(raw).recover(message) {
+                Some(public) if blake2_256(public.as_ref()) == account_id.0 => {
+                    Ok(Some(Crypto::Ecdsa))
+                }
+                _ => Ok(None),
+            }
+        }
+        a => Err(Error::InvalidSignature(a)),
+    }
+}
+
+/// Check signature of 20-byte account over keccak hash of message, as on EVM-compatible chains
+pub fn verify_ethereum(address: &H160, message: &[u8], signature: &[u8]) -> Result<bool, Error> {
+    let raw: [u8; 65] = signature
+        .try_into()
+        .map_err(|_| Error::InvalidSignature(signature.len()))?;
+    match ecdsa::Signature::from_raw(raw).recover_prehashed(&keccak_256(message)) {
+        Some(public) => Ok(ethereum_address(&public) == *address),
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::author::Address;
+
+    /// Hex of signature by development key
+    fn signed(full_address: &str, crypto: Crypto, message: &[u8]) -> (AccountId, String) {
+        let address = Address::from_derivation(full_address, crypto).unwrap();
+        let signature = address.sign(message).unwrap();
+        (address.account_id(), hex::encode(signature.as_bytes()))
+    }
+
+    #[test]
+    fn bytes_wrapping() {
+        assert_eq!(wrap_bytes(b"hello"), b"<Bytes>hello</Bytes>");
+        assert_eq!(wrap_bytes(b"<Bytes>hello</Bytes>"), b"<Bytes>hello</Bytes>");
+        assert_eq!(wrap_bytes(b""), b"<Bytes></Bytes>");
+        assert_eq!(parse_message("0x6869").unwrap(), b"hi");
+        assert_eq!(parse_message("hi").unwrap(), b"hi");
+        assert!(parse_message("0xhi").is_err());
+    }
+
+    #[test]
+    fn signatures_verified_per_crypto() {
+        let message = b"lempi";
+        let phrase = "test test test test test test test test test test test junk";
+        let ethereum = |index| format!("{}/m/44'/60'/0'/0/{}", phrase, index);
+        for (crypto, signer, other) in [
+            (Crypto::Sr25519, "//Alice".to_string(), "//Bob".to_string()),
+            (Crypto::Ed25519, "//Alice".to_string(), "//Bob".to_string()),
+            (Crypto::Ecdsa, "//Alice".to_string(), "//Bob".to_string()),
+            (Crypto::Ethereum, ethereum(0), ethereum(1)),
+        ] {
+            let (account_id, signature) = signed(&signer, crypto, &wrap_bytes(message));
+            assert_eq!(
+                verify(&account_id, message, &signature).unwrap(),
+                (crypto, true)
+            );
+            let (account_id, signature) = signed(&signer, crypto, message);
+            assert_eq!(
+                verify(&account_id, message, &format!("0x{}", signature)).unwrap(),
+                (crypto, false)
+            );
+            assert!(matches!(
+                verify(&account_id, b"other", &signature),
+                Err(Error::NotVerified)
+            ));
+            let (other, _) = signed(&other, crypto, message);
+            assert!(matches!(
+                verify(&other, message, &signature),
+                Err(Error::NotVerified)
+            ));
+        }
+    }
+
+    #[test]
+    fn malformed_signatures() {
+        let (account_id, signature) = signed("//Alice", Crypto::Sr25519, b"lempi");
+        assert!(matches!(
+            verify(&account_id, b"lempi", &signature[2..]),
+            Err(Error::InvalidSignature(63))
+        ));
+        assert!(matches!(
+            verify(&account_id, b"lempi", "0xzz"),
+            Err(Error::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn ethereum_signature() {
+        let pair = ecdsa::Pair::from_seed(&[7; 32]);
+        let address = ethereum_address(&pair.public());
+        let signature = pair.sign_prehashed(&keccak_256(b"payload"));
+        assert!(verify_ethereum(&address, b"payload", &signature.0).unwrap());
+        assert!(!verify_ethereum(&address, b"other payload", &signature.0).unwrap());
+        assert!(verify_ethereum(&address, b"payload", &signature.0[..64]).is_err());
+    }
+}
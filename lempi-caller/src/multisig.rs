@@ -0,0 +1,222 @@
+use parity_scale_codec::{Decode, Encode};
+
+use primitive_types::H256;
+
+use sp_core::{blake2_128, blake2_256, twox_128, twox_64};
+
+use crate::keystore::MultisigEntry;
+
+/// Seed of multisig account id, as in `pallet_multisig`
+const MULTISIG_SEED: &[u8; 16] = b"modlpy/utilisuba";
+
+#[derive(Debug)]
+pub enum Error {
+    DuplicateSignatory,
+    InvalidSignatory(String),
+    InvalidThreshold(u16),
+    TooFewSignatories,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::DuplicateSignatory => write!(f, "signatories must be distinct"),
+            Error::InvalidSignatory(a) => write!(f, "invalid signatory {}", a),
+            Error::InvalidThreshold(a) => write!(f, "threshold {} is out of range", a),
+            Error::TooFewSignatories => write!(f, "multisig needs at least 2 signatories"),
+        }
+    }
+}
+
+/// Multisig account of `pallet_multisig`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Multisig {
+    /// Sorted, as pallet expects them
+    signatories: Vec<H256>,
+    threshold: u16,
+}
+
+impl Multisig {
+    pub fn new(mut signatories: Vec<H256>, threshold: u16) -> Result<Self, Error> {
+        signatories.sort();
+        if signatories.windows(2).any(|a| a[0] == a[1]) {
+            return Err(Error::DuplicateSignatory);
+        }
+        if signatories.len() < 2 {
+            return Err(Error::TooFewSignatories);
+        }
+        if threshold == 0 || threshold as usize > signatories.len() {
+            return Err(Error::InvalidThreshold(threshold));
+        }
+        Ok(Self {
+            signatories,
+            threshold,
+        })
+    }
+
+    pub fn from_entry(entry: &MultisigEntry) -> Result<Self, Error> {
+        let signatories = entry
+            .signatories
+            .iter()
+            .map(|a| match hex::decode(a.trim_start_matches("0x")) {
+                Ok(b) if b.len() == 32 => Ok(H256::from_slice(&b)),
+                _ => Err(Error::InvalidSignatory(a.to_string())),
+            })
+            .collect::<Result<Vec<H256>, Error>>()?;
+        Self::new(signatories, entry.threshold)
+    }
+
+    pub fn to_entry(&self, label: Option<String>) -> MultisigEntry {
+        MultisigEntry {
+            signatories: self.signatories.iter().map(|a| hex::encode(a.0)).collect(),
+            threshold: self.threshold,
+            label,
+        }
+    }
+
+    pub fn signatories(&self) -> &[H256] {
+        &self.signatories
+    }
+
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// Account id: blake2 hash of seed, sorted signatories and threshold
+    pub fn account_id(&self) -> H256 {
+        let signatories: Vec<[u8; 32]> = self.signatories.iter().map(|a| a.0).collect();
+        H256(blake2_256(
+            &(MULTISIG_SEED, signatories, self.threshold).encode(),
+        ))
+    }
+
+    /// Signatories except the one sending transaction, sorted
+    pub fn other_signatories(&self, me: &H256) -> Vec<H256> {
+        self.signatories
+            .iter()
+            .filter(|a| *a != me)
+            .copied()
+            .collect()
+    }
+
+    /// Key of `Multisig.Multisigs` entry for call hash: twox64concat of account, blake2_128concat
+    /// of call hash
+    pub fn storage_key(&self, call_hash: &H256) -> Vec<u8> {
+        let account_id = self.account_id();
+        [
+            &twox_128(b"Multisig")[..],
+            &twox_128(b"Multisigs"),
+            &twox_64(account_id.as_bytes()),
+            account_id.as_bytes(),
+            &blake2_128(call_hash.as_bytes()),
+            call_hash.as_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// Block and extrinsic index of first approval
+#[derive(Clone, Copy, Debug, Decode)]
+pub struct Timepoint {
+    pub height: u32,
+    pub index: u32,
+}
+
+/// Open multisig operation, as in `Multisig.Multisigs` storage
+///
+/// Block number is assumed to be `u32` and balance `u128`, as on all relay chains.
+#[derive(Debug, Decode)]
+pub struct Pending {
+    pub when: Timepoint,
+    pub deposit: u128,
+    pub depositor: [u8; 32],
+    pub approvals: Vec<[u8; 32]>,
+}
+
+impl Pending {
+    pub fn is_approved_by(&self, account: &H256) -> bool {
+        self.approvals.iter().any(|a| a == account.as_fixed_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sp_core::crypto::{AccountId32, Ss58Codec};
+
+    const ALICE: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+    const BOB: &str = "8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48";
+    const CHARLIE: &str = "90b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22";
+
+    fn account(hex_id: &str) -> H256 {
+        H256::from_slice(&hex::decode(hex_id).unwrap())
+    }
+
+    #[test]
+    fn account_id_matches_polkadot_js() {
+        // `createKeyMulti` example of polkadot-js docs: Alice, Bob and Charlie, threshold 2
+        let multisig =
+            Multisig::new(vec![account(ALICE), account(BOB), account(CHARLIE)], 2).unwrap();
+        assert_eq!(
+            AccountId32::from(multisig.account_id().0).to_ss58check_with_version(42u16.into()),
+            "5DjYJStmdZ2rcqXbXGX7TW85JsrW6uG4y9MUcLq2BoPMpRA7"
+        );
+    }
+
+    #[test]
+    fn signatories_are_sorted_and_distinct() {
+        let multisig =
+            Multisig::new(vec![account(CHARLIE), account(ALICE), account(BOB)], 2).unwrap();
+        assert_eq!(
+            multisig.signatories(),
+            &[account(BOB), account(CHARLIE), account(ALICE)]
+        );
+        let other = Multisig::new(vec![account(ALICE), account(BOB), account(CHARLIE)], 2).unwrap();
+        assert_eq!(multisig.account_id(), other.account_id());
+        assert_eq!(
+            multisig.other_signatories(&account(CHARLIE)),
+            vec![account(BOB), account(ALICE)]
+        );
+        assert!(matches!(
+            Multisig::new(vec![account(ALICE), account(BOB), account(ALICE)], 2),
+            Err(Error::DuplicateSignatory)
+        ));
+        assert!(matches!(
+            Multisig::new(vec![account(ALICE)], 1),
+            Err(Error::TooFewSignatories)
+        ));
+        for threshold in [0, 3] {
+            assert!(matches!(
+                Multisig::new(vec![account(ALICE), account(BOB)], threshold),
+                Err(Error::InvalidThreshold(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn storage_key_layout() {
+        let multisig = Multisig::new(vec![account(ALICE), account(BOB)], 2).unwrap();
+        let call_hash = H256([7; 32]);
+        let key = multisig.storage_key(&call_hash);
+        assert_eq!(
+            hex::encode(&key[..32]),
+            "7474449cca95dc5d0c00e71735a6d17d3cd15a3fd6e04e47bee3922dbfa92c8d"
+        );
+        assert_eq!(&key[40..72], multisig.account_id().as_bytes());
+        assert_eq!(&key[88..], call_hash.as_bytes());
+        assert_eq!(key.len(), 32 + 8 + 32 + 16 + 32);
+    }
+
+    #[test]
+    fn pending_decodes_from_storage() {
+        let approvals = vec![account(ALICE).0, account(BOB).0];
+        let encoded = ((12u32, 3u32), 1_000u128, account(ALICE).0, approvals).encode();
+        let pending = Pending::decode(&mut &encoded[..]).unwrap();
+        assert_eq!((pending.when.height, pending.when.index), (12, 3));
+        assert_eq!(pending.deposit, 1_000);
+        assert_eq!(pending.depositor, account(ALICE).0);
+        assert!(pending.is_approved_by(&account(BOB)));
+        assert!(!pending.is_approved_by(&account(CHARLIE)));
+    }
+}
@@ -0,0 +1,349 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crypto_secretbox::{
+    aead::{Aead, KeyInit},
+    Key, Nonce, XSalsa20Poly1305,
+};
+
+use rand::{rngs::OsRng, RngCore};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use sp_core::Pair;
+
+use std::{
+    fs::File,
+    io::{Read, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::author::{Crypto, KeyPair};
+use crate::crypto::Pair as _;
+
+/// Default polkadot-js scrypt parameters, N = 2^15
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+/// Refuse files asking for more work than this, N = 2^20
+const SCRYPT_LOG_N_MAX: u8 = 20;
+/// Memory grows with r, time with r and p; polkadot-js never changed its defaults
+const SCRYPT_R_MAX: u32 = SCRYPT_R;
+const SCRYPT_P_MAX: u32 = 4;
+
+const SALT_LEN: usize = 32;
+/// Salt, then N, p and r as little endian u32
+const SCRYPT_LEN: usize = SALT_LEN + 12;
+const NONCE_LEN: usize = 24;
+
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+
+const SECRET_LEN: usize = 64;
+const SEED_LEN: usize = 32;
+
+const ENCODING_TYPE: [&str; 2] = ["scrypt", "xsalsa20-poly1305"];
+const ENCODING_VERSION: &str = "3";
+
+#[derive(Debug)]
+pub enum Error {
+    DecryptionFailed,
+    EncryptionFailed,
+    File(String),
+    Format(String),
+    Unsupported(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::DecryptionFailed => write!(f, "wrong password or damaged backup"),
+            Error::EncryptionFailed => write!(f, "could not encrypt backup"),
+            Error::File(a) => write!(f, "backup file error: {}", a),
+            Error::Format(a) => write!(f, "backup format error: {}", a),
+            Error::Unsupported(a) => write!(f, "unsupported backup: {}", a),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Encoding {
+    /// `pkcs8` and crypto name
+    pub content: Vec<String>,
+    #[serde(rename = "type")]
+    pub kind: Vec<String>,
+    pub version: String,
+}
+
+/// Account backup, as exported by polkadot-js, Talisman and alike
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Json {
+    /// Base64 of scrypt parameters, nonce and encrypted PKCS8
+    pub encoded: String,
+    pub encoding: Encoding,
+    pub address: String,
+    #[serde(default)]
+    pub meta: Map<String, Value>,
+}
+
+/// Key material recovered from backup
+pub struct DecodedPair {
+    pub crypto: Crypto,
+    /// Full sr25519 secret in ed25519 form, or 32-byte seed, or ed25519 seed with public key
+    pub secret: Vec<u8>,
+}
+
+impl DecodedPair {
+    /// Secret as keystore keeps it
+    ///
+    /// Seeds become hex derivation strings; full sr25519 secret is brought to schnorrkel form,
+    /// that is what `sr25519::Pair::from_seed_slice` expects.
+    pub fn secret_string(&self) -> Result<String, Error> {
+        let secret = match (self.crypto, self.secret.len()) {
+            (Crypto::Sr25519, SECRET_LEN) => {
+                schnorrkel::SecretKey::from_ed25519_bytes(&self.secret)
+                    .map_err(|e| Error::Format(e.to_string()))?
+                    .to_bytes()
+                    .to_vec()
+            }
+            (Crypto::Ed25519, SECRET_LEN) => self.secret[..SEED_LEN].to_vec(),
+            (_, SEED_LEN) => self.secret.clone(),
+            (_, a) => return Err(Error::Format(format!("secret length {}", a))),
+        };
+        Ok(format!("0x{}", hex::encode(secret)))
+    }
+}
+
+impl Json {
+    pub fn read(path: &str) -> Result<Self, Error> {
+        let mut content = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .map_err(|e| Error::File(e.to_string()))?;
+        serde_json::from_str(&content).map_err(|e| Error::Format(e.to_string()))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let content =
+            serde_json::to_string_pretty(&self).map_err(|e| Error::Format(e.to_string()))?;
+        let mut file = File::create(path).map_err(|e| Error::File(e.to_string()))?;
+        file.write_all(content.as_bytes())
+            .map_err(|e| Error::File(e.to_string()))
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.meta
+            .get("name")
+            .and_then(|a| a.as_str())
+            .map(|a| a.to_string())
+    }
+
+    pub fn crypto(&self) -> Result<Crypto, Error> {
+        match self.encoding.content.get(1).map(|a| a.as_str()) {
+            Some("sr25519") => Ok(Crypto::Sr25519),
+            Some("ed25519") => Ok(Crypto::Ed25519),
+            Some("ecdsa") => Ok(Crypto::Ecdsa),
+            Some("ethereum") => Ok(Crypto::Ethereum),
+            a => Err(Error::Unsupported(format!("crypto {:?}", a))),
+        }
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<DecodedPair, Error> {
+        if self.encoding.content.first().map(|a| a.as_str()) != Some("pkcs8") {
+            return Err(Error::Unsupported(format!(
+                "content {:?}",
+                self.encoding.content
+            )));
+        }
+        if self.encoding.kind != ENCODING_TYPE {
+            return Err(Error::Unsupported(format!(
+                "encryption {:?}",
+                self.encoding.kind
+            )));
+        }
+        let crypto = self.crypto()?;
+        let encoded = STANDARD
+            .decode(&self.encoded)
+            .map_err(|e| Error::Format(e.to_string()))?;
+        if encoded.len() < SCRYPT_LEN + NONCE_LEN {
+            return Err(Error::Format("encoded data too short".to_string()));
+        }
+        let (scrypt_params, rest) = encoded.split_at(SCRYPT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let (salt, numbers) = scrypt_params.split_at(SALT_LEN);
+        let number = |i: usize| u32::from_le_bytes(numbers[4 * i..4 * (i + 1)].try_into().unwrap());
+        let (n, p, r) = (number(0), number(1), number(2));
+        if !n.is_power_of_two() || n.trailing_zeros() as u8 > SCRYPT_LOG_N_MAX {
+            return Err(Error::Unsupported(format!("scrypt N {}", n)));
+        }
+        if r > SCRYPT_R_MAX || p > SCRYPT_P_MAX {
+            return Err(Error::Unsupported(format!("scrypt r {}, p {}", r, p)));
+        }
+        let key = password_key(password, salt, n.trailing_zeros() as u8, r, p)?;
+        let decrypted = XSalsa20Poly1305::new(&key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::DecryptionFailed)?;
+
+        if !decrypted.starts_with(&PKCS8_HEADER) {
+            return Err(Error::DecryptionFailed);
+        }
+        let body = &decrypted[PKCS8_HEADER.len()..];
+        // Older backups and ecdsa keep only 32-byte seed
+        let secret_len = [SECRET_LEN, SEED_LEN]
+            .into_iter()
+            .find(|a| body.get(*a..*a + PKCS8_DIVIDER.len()) == Some(&PKCS8_DIVIDER[..]))
+            .ok_or(Error::DecryptionFailed)?;
+        Ok(DecodedPair {
+            crypto,
+            secret: body[..secret_len].to_vec(),
+        })
+    }
+
+    /// Make backup of sr25519 or ed25519 key pair
+    pub fn encrypt(
+        pair: &KeyPair,
+        address: String,
+        name: Option<String>,
+        password: &str,
+    ) -> Result<Self, Error> {
+        let (crypto, secret, public) = match pair {
+            KeyPair::Sr25519(a) => (
+                "sr25519",
+                schnorrkel::SecretKey::from_bytes(&a.to_raw_vec())
+                    .map_err(|e| Error::Format(e.to_string()))?
+                    .to_ed25519_bytes()
+                    .to_vec(),
+                a.public().0.to_vec(),
+            ),
+            KeyPair::Ed25519(a) => (
+                "ed25519",
+                [a.to_raw_vec(), a.public().0.to_vec()].concat(),
+                a.public().0.to_vec(),
+            ),
+            KeyPair::Ecdsa(_) | KeyPair::Ethereum(_) => {
+                return Err(Error::Unsupported(
+                    "only sr25519 and ed25519 keys are exported".to_string(),
+                ))
+            }
+        };
+        let plaintext = [&PKCS8_HEADER[..], &secret, &PKCS8_DIVIDER, &public].concat();
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let key = password_key(password, &salt, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)?;
+        let ciphertext = XSalsa20Poly1305::new(&key)
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+            .map_err(|_| Error::EncryptionFailed)?;
+        let encoded = [
+            &salt[..],
+            &(1u32 << SCRYPT_LOG_N).to_le_bytes(),
+            &SCRYPT_P.to_le_bytes(),
+            &SCRYPT_R.to_le_bytes(),
+            &nonce,
+            &ciphertext,
+        ]
+        .concat();
+
+        let mut meta = Map::new();
+        if let Some(name) = name {
+            meta.insert("name".to_string(), Value::String(name));
+        }
+        let when_created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|a| a.as_millis() as u64)
+            .unwrap_or_default();
+        meta.insert("whenCreated".to_string(), Value::from(when_created));
+
+        Ok(Self {
+            encoded: STANDARD.encode(encoded),
+            encoding: Encoding {
+                content: vec!["pkcs8".to_string(), crypto.to_string()],
+                kind: ENCODING_TYPE.iter().map(|a| a.to_string()).collect(),
+                version: ENCODING_VERSION.to_string(),
+            },
+            address,
+            meta,
+        })
+    }
+}
+
+/// First 32 bytes of scrypt output are the key, as in polkadot-js
+fn password_key(password: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<Key, Error> {
+    let params =
+        scrypt::Params::new(log_n, r, p, 32).map_err(|e| Error::Unsupported(e.to_string()))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| Error::Format(e.to_string()))?;
+    Ok(key.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::author::Address;
+
+    /// Alice, exported from polkadot-js with password `whoisalice`
+    const ALICE: &str = r#"{
+        "encoded": "DumgApKCTqoCty1OZW/8WS+sgo6RdpHhCwAkA2IoDBMAgAAAAQAAAAgAAAB6IG/q24EeVf0JqWqcBd5m2tKq5BlyY84IQ8oamLn9DZe9Ouhgunr7i36J1XxUnTI801axqL/ym1gil0U8440Qvj0lFVKwGuxq38zuifgoj0B3Yru0CI6QKEvQPU5xxj4MpyxdSxP+2PnTzYao0HDH0fulaGvlAYXfqtU89xrx2/z9z7IjSwS3oDFPXRQ9kAdDebtyCVreZ9Otw9v3",
+        "encoding": {
+            "content": ["pkcs8", "sr25519"],
+            "type": ["scrypt", "xsalsa20-poly1305"],
+            "version": "3"
+        },
+        "address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+        "meta": { "genesisHash": "", "name": "Alice", "whenCreated": 1718265838755 }
+    }"#;
+
+    fn address(decoded: &DecodedPair) -> String {
+        Address::from_derivation(&decoded.secret_string().unwrap(), decoded.crypto)
+            .unwrap()
+            .account_id()
+            .name(42)
+    }
+
+    #[test]
+    fn polkadot_js_export() {
+        let json: Json = serde_json::from_str(ALICE).unwrap();
+        assert_eq!(json.name().as_deref(), Some("Alice"));
+        let decoded = json.decrypt("whoisalice").unwrap();
+        assert_eq!(decoded.crypto, Crypto::Sr25519);
+        assert_eq!(address(&decoded), json.address);
+        assert!(matches!(
+            json.decrypt("whoisbob"),
+            Err(Error::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn expensive_scrypt_refused() {
+        let mut json: Json = serde_json::from_str(ALICE).unwrap();
+        let encoded = STANDARD.decode(&json.encoded).unwrap();
+        // r, then p
+        for (offset, value) in [(SALT_LEN + 8, 1u32 << 20), (SALT_LEN + 4, 1u32 << 20)] {
+            let mut changed = encoded.clone();
+            changed[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            json.encoded = STANDARD.encode(changed);
+            assert!(matches!(
+                json.decrypt("whoisalice"),
+                Err(Error::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let json: Json = serde_json::from_str(ALICE).unwrap();
+        let secret = json.decrypt("whoisalice").unwrap().secret_string().unwrap();
+        let pair = match Address::from_derivation(&secret, Crypto::Sr25519).unwrap() {
+            Address::Pair(a) => a,
+            _ => panic!("secret gives key pair"),
+        };
+        let exported =
+            Json::encrypt(&pair, json.address.clone(), json.name(), "new password").unwrap();
+        let decoded = exported.decrypt("new password").unwrap();
+        assert_eq!(address(&decoded), json.address);
+    }
+}
@@ -0,0 +1,670 @@
+use primitive_types::H256;
+
+use crate::author::{
+    generate_mnemonic, normalize_mnemonic, parse_account, parse_ss58, AccountId, Address,
+    AddressBook, Crypto,
+};
+use crate::export::parse_request;
+use crate::extrinsic_builder::{Builder, DetailsCard};
+use crate::message::{parse_message, verify};
+use crate::multisig::Multisig;
+use crate::search::{find_calls, CallEntry};
+use crate::sr25519::vrf::VrfSignData;
+use crate::vrf::{self, describe, parse_transcript};
+
+/// What to do with prompt input once it is submitted
+pub enum Command {
+    /// Nothing to do, question is the result to show
+    Info,
+    Unlock,
+    /// Phrase to import, or number of words for new phrase
+    Mnemonic,
+    /// Signature scheme for key from phrase
+    Crypto(String),
+    /// Derivation path with optional `///password` to apply to phrase
+    Derivation(String, Crypto),
+    /// Optional label for new key
+    SecretLabel(String, Crypto),
+    /// Password to seal derivation string with
+    StoreSecret(String, Crypto, Option<String>),
+    /// SS58 address to watch; `true` for contact
+    WatchOnly(bool),
+    /// Label for watch-only address, optional for own accounts and required for contacts
+    WatchOnlyLabel(H256, bool),
+    /// Own watch-only sr25519 address to derive children from
+    DeriveParent,
+    /// Soft derivation path from parent
+    DerivePath(H256),
+    /// Optional label for derived child
+    DeriveLabel(H256, String),
+    /// Path to polkadot-js JSON backup
+    ImportJson,
+    /// Password of backup at path
+    ImportJsonPassword(String),
+    /// Keystore password for key from backup at path, opened with given password
+    ImportJsonStore(String, String),
+    /// Own unlocked account to back up
+    ExportJson,
+    /// Path to write backup of account to
+    ExportJsonPath(AccountId),
+    /// Password to encrypt backup with
+    ExportJsonPassword(AccountId, String),
+    /// SS58 addresses of multisig signatories
+    MultisigSignatories,
+    /// Number of approvals needed
+    MultisigThreshold(Vec<H256>),
+    /// Optional label for multisig
+    MultisigLabel(Vec<H256>, u16),
+    /// Known multisig to send call from
+    MultisigWrap,
+    /// Whether to send only call hash
+    MultisigApprove(Multisig),
+    /// SS58 address of account author acts for
+    ProxyReal,
+    /// Proxy type to force, by name
+    ProxyType(H256),
+    /// Own unlocked account to sign message with
+    SignAccount,
+    /// Message to sign
+    SignMessage(AccountId),
+    /// SS58 address of message signer
+    VerifyAccount,
+    /// Message that was signed
+    VerifyMessage(AccountId),
+    /// Signature to check
+    VerifySignature(AccountId, Vec<u8>),
+    /// Own unlocked sr25519 account to make VRF signature with, or other account to verify
+    VrfAccount,
+    /// Transcript to sign
+    VrfSign(AccountId),
+    /// Transcript that was signed
+    VrfTranscript(H256),
+    /// VRF signature to check against transcript
+    VrfVerify(H256, VrfSignData),
+    /// Name to save transaction draft as
+    SaveDraft,
+    /// Confirmation to replace existing draft of this name
+    OverwriteDraft(String),
+    /// Name of draft to replace transaction with
+    LoadDraft,
+    /// Hex of call or extrinsic to replace transaction with
+    ImportHex,
+    /// What to export and where
+    Export,
+    /// Query for `pallet.call` names and docs, among all calls
+    FindCall(Vec<CallEntry>),
+    /// Action on list of calls to batch; asked again until empty input
+    Batch,
+    /// CSV file with `address,amount[,memo]` rows
+    BulkTransfers,
+    /// Hex of extrinsic to decode, with optional hash of its birth block
+    Decode,
+}
+
+impl Command {
+    fn preview(&self, input: &str, ss58: u16) -> Option<String> {
+        match self {
+            Command::Derivation(phrase, crypto) => Some(
+                match Address::from_derivation(&format!("{}{}", phrase, input), *crypto) {
+                    Ok(a) => format!("Address: {}", a.name(ss58)),
+                    Err(e) => format!("Invalid derivation: {:?}", e),
+                },
+            ),
+            Command::DerivePath(parent) => {
+                Some(match Address::from_public(*parent).derive_soft(input) {
+                    Ok(a) => format!("Address: {}", a.name(ss58)),
+                    Err(e) => format!("Invalid derivation: {:?}", e),
+                })
+            }
+            Command::FindCall(entries) => {
+                let found = find_calls(entries, input);
+                let mut out = format!("{} calls found", found.len());
+                for entry in found.iter().take(FOUND_SHOWN) {
+                    let docs = entry.docs.split(". ").next().unwrap_or_default();
+                    out += &format!("\r\n{}: {}", entry.name(), docs);
+                }
+                Some(out)
+            }
+            Command::WatchOnly(_) if !input.trim().is_empty() => Some(match parse_ss58(input) {
+                Ok((_, prefix)) if prefix != ss58 => format!(
+                    "Valid address, but for prefix {}; connected chain uses {}",
+                    prefix, ss58
+                ),
+                Ok(_) => "Valid address".to_string(),
+                Err(e) => format!("Invalid address: {:?}", e),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Single line input in details panel, for actions outside of call builder
+pub struct Prompt {
+    pub command: Command,
+    question: String,
+    buffer: String,
+    /// Do not show what is typed
+    secret: bool,
+}
+
+impl Prompt {
+    pub fn new(command: Command, question: &str, secret: bool) -> Self {
+        Self {
+            command,
+            question: question.to_string(),
+            buffer: String::new(),
+            secret,
+        }
+    }
+
+    /// Result to show until dismissed
+    pub fn info(text: &str) -> Self {
+        Self::new(Command::Info, text, false)
+    }
+
+    /// Batched calls with actions on them
+    pub fn batch(builder: &Builder) -> Self {
+        let list = builder.batch_list();
+        let calls = if list.is_empty() {
+            "Batch is empty".to_string()
+        } else {
+            list.iter()
+                .enumerate()
+                .map(|(index, name)| format!("{}. {}", index + 1, name))
+                .collect::<Vec<String>>()
+                .join("\r\n")
+        };
+        Prompt::new(
+            Command::Batch,
+            &format!(
+                "{}\r\n\r\nadd: move current call to batch, or put edited call back; remove N, up N, down N; edit N: take call N out to edit; batch, batch_all or force_batch: make the call. Empty to close:",
+                calls
+            ),
+            false,
+        )
+    }
+
+    pub fn input(&mut self, c: char) {
+        self.buffer.push(c);
+    }
+
+    pub fn backspace(&mut self) {
+        let _ = self.buffer.pop();
+    }
+
+    pub fn paste(&mut self, s: String) {
+        self.buffer.push_str(&s);
+    }
+
+    /// Act on submitted input; this could lead to next prompt in chain
+    pub fn submit(
+        self,
+        address_book: &mut AddressBook,
+        builder: &mut Builder,
+    ) -> Result<Option<Prompt>, String> {
+        let input = self.buffer;
+        match self.command {
+            Command::Info => Ok(None),
+            Command::Unlock => {
+                address_book.unlock(&input);
+                Ok(None)
+            }
+            Command::Mnemonic => {
+                let (phrase, question) = match input.trim() {
+                    "" | "12" | "24" => {
+                        let words = input.trim().parse::<usize>().unwrap_or(12);
+                        let phrase = generate_mnemonic(words).map_err(|e| format!("{:?}", e))?;
+                        let question = format!(
+                            "New mnemonic, write it down:\r\n\r\n{}\r\n\r\nCrypto (sr25519, ed25519, ecdsa, ethereum; empty for sr25519):",
+                            phrase
+                        );
+                        (phrase, question)
+                    }
+                    a => (
+                        normalize_mnemonic(a).map_err(|e| format!("{:?}", e))?,
+                        "Crypto (sr25519, ed25519, ecdsa, ethereum; empty for sr25519):"
+                            .to_string(),
+                    ),
+                };
+                Ok(Some(Prompt::new(Command::Crypto(phrase), &question, false)))
+            }
+            Command::Crypto(phrase) => {
+                let crypto = input.parse::<Crypto>()?;
+                let question = match crypto {
+                    Crypto::Ethereum => "BIP32 path (e.g. /m/44'/60'/0'/0/1, empty for first wallet account), optionally ending with ///password:",
+                    _ => "Derivation path (e.g. //polkadot//0, optionally ending with ///password):",
+                };
+                Ok(Some(Prompt::new(
+                    Command::Derivation(phrase, crypto),
+                    question,
+                    false,
+                )))
+            }
+            Command::Derivation(phrase, crypto) => {
+                let full_address = format!("{}{}", phrase, input.trim());
+                Address::from_derivation(&full_address, crypto).map_err(|e| format!("{:?}", e))?;
+                Ok(Some(Prompt::new(
+                    Command::SecretLabel(full_address, crypto),
+                    "Label (optional):",
+                    false,
+                )))
+            }
+            Command::SecretLabel(full_address, crypto) => Ok(Some(Prompt::new(
+                Command::StoreSecret(full_address, crypto, label(&input)),
+                "Password to encrypt the key in keystore:",
+                true,
+            ))),
+            Command::StoreSecret(full_address, crypto, label) => {
+                address_book
+                    .add_secret(&full_address, crypto, label, &input)
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(None)
+            }
+            Command::WatchOnly(contact) => {
+                let (public, prefix) = parse_ss58(&input).map_err(|e| format!("{:?}", e))?;
+                let label_question = if contact {
+                    "Contact name:"
+                } else {
+                    "Label (optional):"
+                };
+                let question = if prefix != address_book.ss58() {
+                    format!(
+                        "Warning: address prefix {} does not match connected chain prefix {}\r\n\r\n{}",
+                        prefix,
+                        address_book.ss58(),
+                        label_question
+                    )
+                } else {
+                    label_question.to_string()
+                };
+                Ok(Some(Prompt::new(
+                    Command::WatchOnlyLabel(public, contact),
+                    &question,
+                    false,
+                )))
+            }
+            Command::WatchOnlyLabel(public, true) => {
+                let name = label(&input).ok_or("contact name is required")?;
+                address_book
+                    .add_contact(public, name)
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(None)
+            }
+            Command::WatchOnlyLabel(public, false) => {
+                address_book
+                    .add_watch_only(public, label(&input))
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(None)
+            }
+            Command::DeriveParent => {
+                let (parent, _) = parse_ss58(&input).map_err(|e| format!("{:?}", e))?;
+                match address_book.find(&AccountId::Id32(parent)) {
+                    Some(Address::Public(_)) => Ok(Some(Prompt::new(
+                        Command::DerivePath(parent),
+                        "Soft derivation path (e.g. /0 or /savings/1):",
+                        false,
+                    ))),
+                    Some(_) => {
+                        Err("account is unlocked, derive it from secret instead".to_string())
+                    }
+                    None => Err("not an own account".to_string()),
+                }
+            }
+            Command::DerivePath(parent) => {
+                let path = input.trim();
+                if path.is_empty() {
+                    return Err("derivation path is required".to_string());
+                }
+                Address::from_public(parent)
+                    .derive_soft(path)
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(Some(Prompt::new(
+                    Command::DeriveLabel(parent, path.to_string()),
+                    "Label (optional; empty for parent label with path):",
+                    false,
+                )))
+            }
+            Command::DeriveLabel(parent, path) => {
+                address_book
+                    .add_derived_watch_only(parent, &path, label(&input))
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(None)
+            }
+            Command::ImportJson => Ok(Some(Prompt::new(
+                Command::ImportJsonPassword(input.trim().to_string()),
+                "Password of the backup:",
+                true,
+            ))),
+            Command::ImportJsonPassword(path) => Ok(Some(Prompt::new(
+                Command::ImportJsonStore(path, input),
+                "Password to encrypt the key in keystore:",
+                true,
+            ))),
+            Command::ImportJsonStore(path, json_password) => {
+                address_book
+                    .import_json(&path, &json_password, &input)
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(None)
+            }
+            Command::ExportJson => {
+                let account_id = parse_account(&input).map_err(|e| format!("{:?}", e))?;
+                match address_book.find(&account_id) {
+                    Some(Address::Pair(_)) => Ok(Some(Prompt::new(
+                        Command::ExportJsonPath(account_id),
+                        "File to write the backup to:",
+                        false,
+                    ))),
+                    Some(_) => Err("account is not unlocked".to_string()),
+                    None => Err("not an own account".to_string()),
+                }
+            }
+            Command::ExportJsonPath(account_id) => Ok(Some(Prompt::new(
+                Command::ExportJsonPassword(account_id, input.trim().to_string()),
+                "Password to encrypt the backup:",
+                true,
+            ))),
+            Command::ExportJsonPassword(account_id, path) => {
+                address_book
+                    .export_json(&account_id, &path, &input)
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(None)
+            }
+            Command::MultisigSignatories => {
+                let signatories = input
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|a| !a.is_empty())
+                    .map(|a| parse_ss58(a).map(|(b, _)| b))
+                    .collect::<Result<Vec<H256>, _>>()
+                    .map_err(|e| format!("{:?}", e))?;
+                let question = format!("Threshold, 1 to {}:", signatories.len());
+                Ok(Some(Prompt::new(
+                    Command::MultisigThreshold(signatories),
+                    &question,
+                    false,
+                )))
+            }
+            Command::MultisigThreshold(signatories) => {
+                let threshold = input
+                    .trim()
+                    .parse::<u16>()
+                    .map_err(|e| format!("threshold: {}", e))?;
+                Multisig::new(signatories.clone(), threshold).map_err(|e| e.to_string())?;
+                Ok(Some(Prompt::new(
+                    Command::MultisigLabel(signatories, threshold),
+                    "Label (optional):",
+                    false,
+                )))
+            }
+            Command::MultisigLabel(signatories, threshold) => {
+                address_book
+                    .add_multisig(signatories, threshold, label(&input))
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(None)
+            }
+            Command::MultisigWrap => {
+                let (account_id, _) = parse_ss58(&input).map_err(|e| format!("{:?}", e))?;
+                let multisig = address_book
+                    .multisig(&account_id)
+                    .ok_or("not a known multisig, define it first")?
+                    .clone();
+                Ok(Some(Prompt::new(
+                    Command::MultisigApprove(multisig),
+                    "Send full call with as_multi (empty), or only its hash with approve_as_multi (approve):",
+                    false,
+                )))
+            }
+            Command::MultisigApprove(multisig) => {
+                let approve_only = match input.trim() {
+                    "" => false,
+                    "approve" => true,
+                    a => return Err(format!("unexpected answer {}", a)),
+                };
+                builder.start_multisig(multisig, approve_only)?;
+                Ok(None)
+            }
+            Command::ProxyReal => {
+                let (real, _) = parse_ss58(&input).map_err(|e| format!("{:?}", e))?;
+                Ok(Some(Prompt::new(
+                    Command::ProxyType(real),
+                    "Proxy type (e.g. Any, Staking; empty for any delegation of author):",
+                    false,
+                )))
+            }
+            Command::ProxyType(real) => {
+                builder.start_proxy(real, label(&input))?;
+                Ok(None)
+            }
+            Command::SignAccount => {
+                let account_id = parse_account(&input).map_err(|e| format!("{:?}", e))?;
+                match address_book.find(&account_id) {
+                    Some(Address::Pair(_)) => Ok(Some(Prompt::new(
+                        Command::SignMessage(account_id),
+                        "Message, text or 0x-prefixed hex; it is signed wrapped in <Bytes>...</Bytes>:",
+                        false,
+                    ))),
+                    Some(_) => Err("account is not unlocked".to_string()),
+                    None => Err("not an own account".to_string()),
+                }
+            }
+            Command::SignMessage(account_id) => {
+                let message = parse_message(&input).map_err(|e| e.to_string())?;
+                let signature = address_book
+                    .sign_message(&account_id, &message)
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(Some(Prompt::info(&format!(
+                    "{} signature by {}:\r\n\r\n0x{}",
+                    signature.variant_name(),
+                    account_id.name(address_book.ss58()),
+                    hex::encode(signature.as_bytes())
+                ))))
+            }
+            Command::VerifyAccount => {
+                let account_id = parse_account(&input).map_err(|e| format!("{:?}", e))?;
+                Ok(Some(Prompt::new(
+                    Command::VerifyMessage(account_id),
+                    "Message, text or 0x-prefixed hex:",
+                    false,
+                )))
+            }
+            Command::VerifyMessage(account_id) => {
+                let message = parse_message(&input).map_err(|e| e.to_string())?;
+                Ok(Some(Prompt::new(
+                    Command::VerifySignature(account_id, message),
+                    "Signature, hex:",
+                    false,
+                )))
+            }
+            Command::VerifySignature(account_id, message) => {
+                let result = match verify(&account_id, &message, &input) {
+                    Ok((crypto, true)) => {
+                        format!("Valid {:?} signature over <Bytes>-wrapped message", crypto)
+                    }
+                    Ok((crypto, false)) => {
+                        format!("Valid {:?} signature over bare message", crypto)
+                    }
+                    Err(e) => format!("Not valid: {}", e),
+                };
+                Ok(Some(Prompt::info(&result)))
+            }
+            Command::VrfAccount => {
+                let account_id = parse_account(&input).map_err(|e| format!("{:?}", e))?;
+                match (address_book.find(&account_id), account_id) {
+                    (Some(Address::Pair(_)), _) => Ok(Some(Prompt::new(
+                        Command::VrfSign(account_id),
+                        TRANSCRIPT_QUESTION,
+                        false,
+                    ))),
+                    (_, AccountId::Id32(a)) => Ok(Some(Prompt::new(
+                        Command::VrfTranscript(a),
+                        TRANSCRIPT_QUESTION,
+                        false,
+                    ))),
+                    (_, AccountId::Id20(_)) => Err("VRF needs sr25519 account".to_string()),
+                }
+            }
+            Command::VrfSign(account_id) => {
+                let data = parse_transcript(&input).map_err(|e| e.to_string())?;
+                let signature = address_book
+                    .vrf_sign(&account_id, &data)
+                    .map_err(|e| format!("{:?}", e))?;
+                Ok(Some(Prompt::info(&format!(
+                    "VRF signature by {}:\r\n\r\n{}",
+                    account_id.name(address_book.ss58()),
+                    describe(&signature)
+                ))))
+            }
+            Command::VrfTranscript(public) => {
+                let data = parse_transcript(&input).map_err(|e| e.to_string())?;
+                Ok(Some(Prompt::new(
+                    Command::VrfVerify(public, data),
+                    "VRF signature, pre-output and proof, hex:",
+                    false,
+                )))
+            }
+            Command::VrfVerify(public, data) => {
+                let result = match vrf::verify(&public, &data, &input) {
+                    Ok(()) => "Valid VRF signature".to_string(),
+                    Err(e) => format!("Not valid: {}", e),
+                };
+                Ok(Some(Prompt::info(&result)))
+            }
+            Command::SaveDraft => {
+                let name = input.trim().to_string();
+                if builder.draft_exists(&name) {
+                    let question = format!("Draft {} exists; type yes to replace it:", name);
+                    Ok(Some(Prompt::new(
+                        Command::OverwriteDraft(name),
+                        &question,
+                        false,
+                    )))
+                } else {
+                    builder.save_draft(&name, false)?;
+                    Ok(Some(Prompt::info(&format!("Draft {} saved", name))))
+                }
+            }
+            Command::OverwriteDraft(name) => {
+                if input.trim() == "yes" {
+                    builder.save_draft(&name, true)?;
+                    Ok(Some(Prompt::info(&format!("Draft {} replaced", name))))
+                } else {
+                    Ok(Some(Prompt::info(&format!("Draft {} kept", name))))
+                }
+            }
+            Command::LoadDraft => {
+                let warnings = builder.load_draft(&input)?;
+                let result = if warnings.is_empty() {
+                    format!("Draft {} loaded", input.trim())
+                } else {
+                    format!(
+                        "Draft {} loaded with warnings:\r\n\r\n{}",
+                        input.trim(),
+                        warnings.join("\r\n")
+                    )
+                };
+                Ok(Some(Prompt::info(&result)))
+            }
+            Command::ImportHex => {
+                let (warnings, signed) = builder.import_hex(&input)?;
+                let mut result = "Call imported".to_string();
+                if signed {
+                    result += "; signature and extensions were dropped, sign it anew";
+                }
+                if !warnings.is_empty() {
+                    result += &format!(" with warnings:\r\n\r\n{}", warnings.join("\r\n"));
+                }
+                Ok(Some(Prompt::info(&result)))
+            }
+            Command::Decode => {
+                let mut parts = input.split_whitespace();
+                let extrinsic = parts.next().ok_or("extrinsic hex is empty".to_string())?;
+                let decoded = builder.decode(extrinsic, parts.next())?;
+                let text = serde_json::to_string_pretty(&decoded).map_err(|e| e.to_string())?;
+                Ok(Some(Prompt::info(&text.replace('\n', "\r\n"))))
+            }
+            Command::Export => {
+                let (kind, file) = parse_request(&input).map_err(|e| e.to_string())?;
+                let target = builder.export(kind, file.as_deref())?;
+                Ok(Some(Prompt::info(&format!(
+                    "{} exported to {}",
+                    kind.name(),
+                    target
+                ))))
+            }
+            Command::Batch => {
+                let mut parts = input.split_whitespace();
+                let action = match parts.next() {
+                    Some(a) => a,
+                    None => return Ok(None),
+                };
+                let index = match parts.next().map(|a| a.parse::<usize>()) {
+                    Some(Ok(a)) if a > 0 => Some(a - 1),
+                    Some(_) => return Err(format!("invalid call number in {}", input.trim())),
+                    None => None,
+                };
+                match (action, index) {
+                    ("add", None) => builder.batch_add()?,
+                    ("remove", Some(a)) => builder.batch_remove(a)?,
+                    ("up", Some(a)) => builder.batch_move(a, true)?,
+                    ("down", Some(a)) => builder.batch_move(a, false)?,
+                    ("edit", Some(a)) => builder.batch_edit(a)?,
+                    (a @ ("batch" | "batch_all" | "force_batch"), None) => {
+                        builder.batch_finish(a)?;
+                        return Ok(None);
+                    }
+                    _ => return Err(format!("unknown batch action {}", input.trim())),
+                }
+                Ok(Some(Prompt::batch(builder)))
+            }
+            Command::BulkTransfers => match builder.bulk_transfers(&input) {
+                Ok(a) => Ok(Some(Prompt::info(&format!(
+                    "Utility.batch_all made: {}\r\n\r\nCheck the call before signing",
+                    a
+                )))),
+                Err(e) => Ok(Some(Prompt::info(&format!("No call made: {}", e)))),
+            },
+            Command::FindCall(entries) => match find_calls(&entries, &input).first() {
+                Some(a) => {
+                    builder.find_call(a)?;
+                    Ok(None)
+                }
+                None => Err(format!("no call matches {}", input.trim())),
+            },
+        }
+    }
+
+    pub fn details(&self, ss58: u16) -> DetailsCard {
+        let buffer = if self.secret {
+            "*".repeat(self.buffer.chars().count())
+        } else {
+            self.buffer.clone()
+        };
+        let (content, buffer) = match self.command {
+            Command::Info => ("Enter or Escape to close".to_string(), None),
+            _ => (
+                "Enter to submit, Escape to cancel".to_string(),
+                Some(buffer),
+            ),
+        };
+        DetailsCard {
+            content,
+            info: self.question.clone(),
+            buffer,
+            preview: self.command.preview(&self.buffer, ss58),
+            selector: None,
+        }
+    }
+}
+
+/// Calls listed in find call preview
+const FOUND_SHOWN: usize = 10;
+
+const TRANSCRIPT_QUESTION: &str =
+    "Transcript, label then domains with data, text or 0x-prefixed hex (e.g. BABE; slot: 0x2a00000000000000):";
+
+/// Empty input means no label
+fn label(input: &str) -> Option<String> {
+    match input.trim() {
+        "" => None,
+        a => Some(a.to_string()),
+    }
+}
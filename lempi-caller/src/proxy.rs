@@ -0,0 +1,80 @@
+use parity_scale_codec::Decode;
+
+use primitive_types::H256;
+
+use sp_core::{twox_128, twox_64};
+
+/// Single delegation of `pallet_proxy`
+///
+/// Proxy type is assumed to be fieldless enum, so it is just variant index.
+#[derive(Debug, Decode)]
+pub struct ProxyDefinition {
+    pub delegate: [u8; 32],
+    pub proxy_type: u8,
+    pub delay: u32,
+}
+
+/// Delegations of real account with deposit, as in `Proxy.Proxies` storage
+#[derive(Debug, Decode)]
+pub struct Proxies {
+    pub definitions: Vec<ProxyDefinition>,
+    pub deposit: u128,
+}
+
+impl Proxies {
+    /// Delegations given to this account
+    pub fn of_delegate(&self, delegate: &H256) -> Vec<&ProxyDefinition> {
+        self.definitions
+            .iter()
+            .filter(|a| a.delegate == delegate.0)
+            .collect()
+    }
+}
+
+/// Key of `Proxy.Proxies` entry: twox64concat of real account
+pub fn storage_key(real: &H256) -> Vec<u8> {
+    [
+        &twox_128(b"Proxy")[..],
+        &twox_128(b"Proxies"),
+        &twox_64(real.as_bytes()),
+        real.as_bytes(),
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use parity_scale_codec::Encode;
+
+    #[test]
+    fn storage_key_layout() {
+        let real = H256([1; 32]);
+        let key = storage_key(&real);
+        assert_eq!(
+            hex::encode(&key[..32]),
+            "1809d78346727a0ef58c0fa03bafa3231d885dcfb277f185f2d8e62a5f290c85"
+        );
+        assert_eq!(&key[32..40], &twox_64(real.as_bytes()));
+        assert_eq!(&key[40..], real.as_bytes());
+    }
+
+    #[test]
+    fn delegations_of_delegate() {
+        // (delegate, proxy type, delay) and deposit, as `Proxy.Proxies` keeps them
+        let definitions = vec![([2u8; 32], 0u8, 0u32), ([3; 32], 1, 10), ([2; 32], 3, 5)];
+        let encoded = (definitions, 500u128).encode();
+        let proxies = Proxies::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(proxies.deposit, 500);
+        let delegations = proxies.of_delegate(&H256([2; 32]));
+        assert_eq!(
+            delegations
+                .iter()
+                .map(|a| (a.proxy_type, a.delay))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (3, 5)]
+        );
+        assert!(proxies.of_delegate(&H256([4; 32])).is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+use frame_metadata::v15::RuntimeMetadataV15;
+
+use scale_info::TypeDef;
+
+/// Call of some pallet, as listed in metadata
+#[derive(Clone, Debug)]
+pub struct CallEntry {
+    pub pallet: String,
+    pub call: String,
+    pub docs: String,
+}
+
+impl CallEntry {
+    /// `pallet.call`
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.pallet, self.call)
+    }
+}
+
+/// All calls of all pallets, in metadata order
+pub fn calls(metadata: &RuntimeMetadataV15) -> Vec<CallEntry> {
+    let mut out = Vec::new();
+    for pallet in metadata.pallets.iter() {
+        let ty = pallet
+            .calls
+            .as_ref()
+            .and_then(|a| metadata.types.resolve(a.ty.id));
+        if let Some(TypeDef::Variant(a)) = ty.map(|b| &b.type_def) {
+            for variant in a.variants.iter() {
+                out.push(CallEntry {
+                    pallet: pallet.name.to_owned(),
+                    call: variant.name.to_owned(),
+                    docs: variant.docs.join(" "),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Calls matching query, best first
+///
+/// Names are matched fuzzily; calls with only docs containing the query come after all name
+/// matches.
+pub fn find_calls<'a>(entries: &'a [CallEntry], query: &str) -> Vec<&'a CallEntry> {
+    let query = query.trim();
+    let lowercase = query.to_lowercase();
+    let mut found: Vec<(bool, (usize, usize), &CallEntry)> = entries
+        .iter()
+        .filter_map(|a| match fuzzy_score(query, &a.name()) {
+            Some(score) => Some((false, score, a)),
+            None if a.docs.to_lowercase().contains(&lowercase) => Some((true, (0, 0), a)),
+            None => None,
+        })
+        .collect();
+    found.sort_by_key(|(docs_only, score, _)| (*docs_only, *score));
+    found.into_iter().map(|(_, _, a)| a).collect()
+}
+
+/// Case-insensitive fuzzy match: all pattern characters appear in text in order
+///
+/// Score is number of skipped characters between matched ones, then position of first match;
+/// lower is better, so substrings come first.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<(usize, usize)> {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let first = match pattern.first() {
+        Some(a) => a,
+        None => return Some((0, 0)),
+    };
+    let mut best = None;
+    for start in (0..text.len()).filter(|a| text[*a] == *first) {
+        let mut gaps = 0;
+        let mut position = start;
+        let mut matched = true;
+        for c in pattern[1..].iter() {
+            match text[position + 1..].iter().position(|a| a == c) {
+                Some(skipped) => {
+                    gaps += skipped;
+                    position += skipped + 1;
+                }
+                None => {
+                    matched = false;
+                    break;
+                }
+            }
+        }
+        if !matched {
+            break;
+        }
+        if best.map(|a| (gaps, start) < a).unwrap_or(true) {
+            best = Some((gaps, start));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<CallEntry> {
+        [
+            (
+                "System",
+                "remark",
+                "Make some on-chain remark; transfers nothing",
+            ),
+            ("Scattered", "t_r_a_n_s_f_e_r", ""),
+            (
+                "Balances",
+                "transfer_keep_alive",
+                "Same as transfer, but keeps sender alive",
+            ),
+            ("Assets", "transfer", ""),
+        ]
+        .iter()
+        .map(|(pallet, call, docs)| CallEntry {
+            pallet: pallet.to_string(),
+            call: call.to_string(),
+            docs: docs.to_string(),
+        })
+        .collect()
+    }
+
+    fn names(found: Vec<&CallEntry>) -> Vec<String> {
+        found.iter().map(|a| a.name()).collect()
+    }
+
+    #[test]
+    fn substrings_first_then_scattered_then_docs() {
+        let entries = entries();
+        let expected = [
+            "Assets.transfer",
+            "Balances.transfer_keep_alive",
+            "Scattered.t_r_a_n_s_f_e_r",
+            "System.remark",
+        ];
+        assert_eq!(names(find_calls(&entries, "transfer")), expected);
+        assert_eq!(names(find_calls(&entries, " TransFer ")), expected);
+    }
+
+    #[test]
+    fn docs_only_matches() {
+        let entries = entries();
+        assert_eq!(names(find_calls(&entries, "nothing")), ["System.remark"]);
+        assert_eq!(
+            names(find_calls(&entries, "SENDER")),
+            ["Balances.transfer_keep_alive"]
+        );
+        assert!(find_calls(&entries, "vesting").is_empty());
+    }
+
+    #[test]
+    fn empty_query_lists_everything() {
+        let entries = entries();
+        let all: Vec<String> = entries.iter().map(|a| a.name()).collect();
+        assert_eq!(names(find_calls(&entries, "")), all);
+        assert_eq!(names(find_calls(&entries, "  ")), all);
+    }
+
+    #[test]
+    fn fuzzy_scores() {
+        assert_eq!(fuzzy_score("bond", "Staking.bond"), Some((0, 8)));
+        assert_eq!(fuzzy_score("BOND", "staking.bond"), Some((0, 8)));
+        assert_eq!(fuzzy_score("sb", "Staking.bond"), Some((7, 0)));
+        // later start with fewer gaps wins
+        assert_eq!(fuzzy_score("ab", "a_b ab"), Some((0, 4)));
+        assert_eq!(fuzzy_score("ba", "ab"), None);
+        assert_eq!(fuzzy_score("", ""), Some((0, 0)));
+    }
+}
@@ -2,25 +2,22 @@
 //!
 //! Note: `CHAIN_CODE_LENGTH` must be equal to `crate::crypto::JUNCTION_ID_LEN`
 //! for this to work.
-use crate::crypto::{DeriveError, Pair as TraitPair, SecretStringError};
+use crate::crypto::{DeriveError, DeriveJunction, Pair as TraitPair, SecretStringError};
 use schnorrkel::{
-	derive::CHAIN_CODE_LENGTH, signing_context, ExpansionMode, Keypair, MiniSecretKey, SecretKey,
+	derive::{ChainCode, Derivation, CHAIN_CODE_LENGTH},
+	signing_context, ExpansionMode, Keypair, MiniSecretKey, PublicKey, SecretKey,
 };
-use sp_std::vec::Vec;
-
-use crate::{
-	crypto::{
-		ByteArray, CryptoType, CryptoTypeId, Derive, FromEntropy, Public as TraitPublic,
-		UncheckedFrom,
-	},
-	hash::{H256, H512},
+
+use crate::crypto::{
+	ByteArray, CryptoType, CryptoTypeId, Derive, FromEntropy, Public as TraitPublic, Ss58Codec,
+	UncheckedFrom,
 };
-use codec::{Decode, Encode, MaxEncodedLen};
-use scale_info::TypeInfo;
-use sp_std::ops::Deref;
+use parity_scale_codec::{self as codec, Decode, Encode, MaxEncodedLen};
+use primitive_types::{H256, H512};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::Deref;
 
 use schnorrkel::keys::{MINI_SECRET_KEY_LENGTH, SECRET_KEY_LENGTH};
-use sp_runtime_interface::pass_by::PassByInner;
 
 // signing context
 const SIGNING_CTX: &[u8] = b"substrate";
@@ -39,9 +36,7 @@ pub const CRYPTO_ID: CryptoTypeId = CryptoTypeId(*b"sr25");
 	Encode,
         Hash,
 	Decode,
-	PassByInner,
 	MaxEncodedLen,
-	TypeInfo,
 )]
 pub struct Public(pub [u8; 32]);
 
@@ -143,21 +138,34 @@ impl std::fmt::Display for Public {
 	}
 }
 
-impl sp_std::fmt::Debug for Public {
-	#[cfg(feature = "std")]
-	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+impl std::fmt::Debug for Public {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
 		let s = self.to_ss58check();
-		write!(f, "{} ({}...)", crate::hexdisplay::HexDisplay::from(&self.0), &s[0..8])
+		write!(f, "{} ({}...)", hex::encode(self.0), &s[0..8])
+	}
+}
+
+impl Serialize for Public {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_str(&self.to_ss58check())
 	}
+}
 
-	#[cfg(not(feature = "std"))]
-	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
-		Ok(())
+impl<'de> Deserialize<'de> for Public {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		Public::from_ss58check(&String::deserialize(deserializer)?)
+			.map_err(|e| de::Error::custom(format!("{:?}", e)))
 	}
 }
 
 /// An Schnorrkel/Ristretto x25519 ("sr25519") signature.
-#[derive(Encode, Decode, Hash, MaxEncodedLen, PassByInner, TypeInfo, PartialEq, Eq)]
+#[derive(Encode, Decode, Hash, MaxEncodedLen, PartialEq, Eq)]
 pub struct Signature(pub [u8; 64]);
 
 impl TryFrom<&[u8]> for Signature {
@@ -212,15 +220,36 @@ impl AsMut<[u8]> for Signature {
 	}
 }
 
+impl Serialize for Signature {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_str(&hex::encode(self))
+	}
+}
+
+impl<'de> Deserialize<'de> for Signature {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let signature_hex = hex::decode(String::deserialize(deserializer)?)
+			.map_err(|e| de::Error::custom(format!("{:?}", e)))?;
+		Signature::try_from(signature_hex.as_ref())
+			.map_err(|e| de::Error::custom(format!("{:?}", e)))
+	}
+}
+
 impl From<schnorrkel::Signature> for Signature {
 	fn from(s: schnorrkel::Signature) -> Signature {
 		Signature(s.to_bytes())
 	}
 }
 
-impl sp_std::fmt::Debug for Signature {
-	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
-		write!(f, "{}", crate::hexdisplay::HexDisplay::from(&self.0))
+impl std::fmt::Debug for Signature {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", hex::encode(self.0))
 	}
 }
 
@@ -267,7 +296,6 @@ impl Derive for Public {
 	/// Derive a child key from a series of given junctions.
 	///
 	/// `None` if there are any hard junctions in there.
-	#[cfg(feature = "serde")]
 	fn derive<Iter: Iterator<Item = DeriveJunction>>(&self, path: Iter) -> Option<Public> {
 		let mut acc = PublicKey::from_bytes(self.as_ref()).ok()?;
 		for j in path {
@@ -309,35 +337,30 @@ impl ByteArray for Public {
 
 impl TraitPublic for Public {}
 
-#[cfg(feature = "std")]
 impl From<MiniSecretKey> for Pair {
 	fn from(sec: MiniSecretKey) -> Pair {
 		Pair(sec.expand_to_keypair(ExpansionMode::Ed25519))
 	}
 }
 
-#[cfg(feature = "std")]
 impl From<SecretKey> for Pair {
 	fn from(sec: SecretKey) -> Pair {
 		Pair(Keypair::from(sec))
 	}
 }
 
-#[cfg(feature = "full_crypto")]
 impl From<schnorrkel::Keypair> for Pair {
 	fn from(p: schnorrkel::Keypair) -> Pair {
 		Pair(p)
 	}
 }
 
-#[cfg(feature = "full_crypto")]
 impl From<Pair> for schnorrkel::Keypair {
 	fn from(p: Pair) -> schnorrkel::Keypair {
 		p.0
 	}
 }
 
-#[cfg(feature = "full_crypto")]
 impl AsRef<schnorrkel::Keypair> for Pair {
 	fn as_ref(&self) -> &schnorrkel::Keypair {
 		&self.0
@@ -345,16 +368,13 @@ impl AsRef<schnorrkel::Keypair> for Pair {
 }
 
 /// Derive a single hard junction.
-#[cfg(feature = "full_crypto")]
 fn derive_hard_junction(secret: &SecretKey, cc: &[u8; CHAIN_CODE_LENGTH]) -> MiniSecretKey {
 	secret.hard_derive_mini_secret_key(Some(ChainCode(*cc)), b"").0
 }
 
 /// The raw secret seed, which can be used to recreate the `Pair`.
-#[cfg(feature = "full_crypto")]
 type Seed = [u8; MINI_SECRET_KEY_LENGTH];
 
-#[cfg(feature = "full_crypto")]
 impl TraitPair for Pair {
 	type Public = Public;
 	type Seed = Seed;
@@ -425,7 +445,6 @@ impl TraitPair for Pair {
 	}
 }
 
-#[cfg(feature = "std")]
 impl Pair {
 	/// Verify a signature on a message. Returns `true` if the signature is good.
 	/// Supports old 0.1.1 deprecated signatures and should be used only for backward
@@ -443,16 +462,13 @@ impl Pair {
 }
 
 impl CryptoType for Public {
-	#[cfg(feature = "full_crypto")]
 	type Pair = Pair;
 }
 
 impl CryptoType for Signature {
-	#[cfg(feature = "full_crypto")]
 	type Pair = Pair;
 }
 
-#[cfg(feature = "full_crypto")]
 impl CryptoType for Pair {
 	type Pair = Pair;
 }
@@ -460,7 +476,6 @@ impl CryptoType for Pair {
 /// Schnorrkel VRF related types and operations.
 pub mod vrf {
 	use super::*;
-	#[cfg(feature = "full_crypto")]
 	use crate::crypto::VrfSecret;
 	use crate::crypto::{VrfCrypto, VrfPublic};
 	use schnorrkel::{
@@ -536,7 +551,7 @@ pub mod vrf {
 	}
 
 	/// VRF signature data
-	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, MaxEncodedLen, TypeInfo)]
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, MaxEncodedLen)]
 	pub struct VrfSignature {
 		/// VRF pre-output.
 		pub pre_output: VrfPreOutput,
@@ -567,14 +582,6 @@ pub mod vrf {
 		}
 	}
 
-	impl TypeInfo for VrfPreOutput {
-		type Identity = [u8; VRF_PREOUT_LENGTH];
-
-		fn type_info() -> scale_info::Type {
-			Self::Identity::type_info()
-		}
-	}
-
 	/// VRF proof type suitable for schnorrkel operations.
 	#[derive(Clone, Debug, PartialEq, Eq)]
 	pub struct VrfProof(pub schnorrkel::vrf::VRFProof);
@@ -598,15 +605,6 @@ pub mod vrf {
 		}
 	}
 
-	impl TypeInfo for VrfProof {
-		type Identity = [u8; VRF_PROOF_LENGTH];
-
-		fn type_info() -> scale_info::Type {
-			Self::Identity::type_info()
-		}
-	}
-
-	#[cfg(feature = "full_crypto")]
 	impl VrfCrypto for Pair {
 		type VrfInput = VrfTranscript;
 		type VrfPreOutput = VrfPreOutput;
@@ -614,7 +612,6 @@ pub mod vrf {
 		type VrfSignature = VrfSignature;
 	}
 
-	#[cfg(feature = "full_crypto")]
 	impl VrfSecret for Pair {
 		fn vrf_sign(&self, data: &Self::VrfSignData) -> Self::VrfSignature {
 			let inout = self.0.vrf_create_hash(data.transcript.0.clone());
@@ -695,7 +692,6 @@ pub mod vrf {
 		}
 	}
 
-	#[cfg(feature = "full_crypto")]
 	impl Pair {
 		/// Generate output bytes from the given VRF configuration.
 		pub fn make_bytes<const N: usize>(&self, context: &[u8], input: &VrfInput) -> [u8; N]
@@ -0,0 +1,339 @@
+use serde_json::{Map, Value};
+
+use substrate_constructor::fill_prepare::{PrimitiveToFill, TypeContentToFill, UnsignedToFill};
+
+use substrate_parser::special_indicators::SpecialtyUnsignedInteger;
+
+#[derive(Debug)]
+pub enum Error {
+    NotANumber(String),
+    TooManyDecimals(u64),
+    TooPrecise(u8),
+    Overflow,
+    UnknownUnit(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NotANumber(a) => write!(f, "not a number: {}", a),
+            Error::TooManyDecimals(a) => {
+                write!(f, "token with {} decimals does not fit into u128", a)
+            }
+            Error::TooPrecise(a) => write!(f, "too many decimal places, at most {} allowed", a),
+            Error::Overflow => write!(f, "value does not fit into u128"),
+            Error::UnknownUnit(a) => write!(f, "unknown unit: {}", a),
+        }
+    }
+}
+
+/// Largest number of decimals with whole token still fitting into u128
+const MAX_DECIMALS: u64 = 38;
+
+/// Native token denomination of connected chain
+///
+/// Read from `tokenDecimals` and `tokenSymbol` of system properties; both could come as single
+/// values or as arrays with native token first.
+#[derive(Clone, Debug)]
+pub struct Units {
+    decimals: u8,
+    symbol: String,
+}
+
+impl Units {
+    pub fn from_specs(specs: &Map<String, Value>) -> Result<Self, Error> {
+        let decimals = match specs.get("tokenDecimals") {
+            Some(Value::Number(a)) => a.as_u64(),
+            Some(Value::Array(a)) => a.first().and_then(|b| b.as_u64()),
+            _ => None,
+        }
+        .unwrap_or(0);
+        if decimals > MAX_DECIMALS {
+            return Err(Error::TooManyDecimals(decimals));
+        }
+        let symbol = match specs.get("tokenSymbol") {
+            Some(Value::String(a)) => a.to_owned(),
+            Some(Value::Array(a)) => a
+                .first()
+                .and_then(|b| b.as_str())
+                .unwrap_or("UNIT")
+                .to_owned(),
+            _ => "UNIT".to_owned(),
+        };
+        Ok(Self {
+            decimals: decimals as u8,
+            symbol,
+        })
+    }
+
+    /// Bare planck, for chains with token that could not be used
+    pub fn planck() -> Self {
+        Self {
+            decimals: 0,
+            symbol: "planck".to_owned(),
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// Print planck value in tokens, e.g. `1.5 WND`
+    pub fn format(&self, planck: u128) -> String {
+        let unit = 10u128.pow(self.decimals as u32);
+        let integer = planck / unit;
+        let fraction = planck % unit;
+        if fraction == 0 {
+            format!("{} {}", integer, self.symbol)
+        } else {
+            let fraction = format!("{:0width$}", fraction, width = self.decimals as usize);
+            format!(
+                "{}.{} {}",
+                integer,
+                fraction.trim_end_matches('0'),
+                self.symbol
+            )
+        }
+    }
+
+    /// Read user input into planck value
+    ///
+    /// Plain numbers are tokens; `1.5 WND`, `1.5`, `250m`, `250 mWND` and `12 planck` are all
+    /// accepted. SI prefixes `k`, `m`, `u` (or `µ`) and `n` scale the token.
+    pub fn parse(&self, input: &str) -> Result<u128, Error> {
+        let input = input.trim();
+        let split = input
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+            .unwrap_or(input.len());
+        let (number, unit) = input.split_at(split);
+        let number = number.replace('_', "");
+        let unit = unit.trim();
+        if number.is_empty() {
+            return Err(Error::NotANumber(input.to_owned()));
+        }
+
+        let exponent: i32 = if unit.eq_ignore_ascii_case("planck") {
+            0
+        } else if unit.is_empty() || unit.eq_ignore_ascii_case(&self.symbol) {
+            self.decimals as i32
+        } else {
+            let mut chars = unit.chars();
+            let shift = match chars.next() {
+                Some('k') => 3,
+                Some('m') => -3,
+                Some('u') | Some('µ') => -6,
+                Some('n') => -9,
+                _ => return Err(Error::UnknownUnit(unit.to_owned())),
+            };
+            let rest = chars.as_str();
+            if !rest.is_empty() && !rest.eq_ignore_ascii_case(&self.symbol) {
+                return Err(Error::UnknownUnit(unit.to_owned()));
+            }
+            self.decimals as i32 + shift
+        };
+
+        let (integer, fraction) = match number.split_once('.') {
+            Some((a, b)) => (a, b),
+            None => (number.as_str(), ""),
+        };
+        if integer.is_empty() && fraction.is_empty() || fraction.contains('.') {
+            return Err(Error::NotANumber(input.to_owned()));
+        }
+        let fraction = fraction.trim_end_matches('0');
+        if exponent < 0 || fraction.len() > exponent as usize {
+            return Err(Error::TooPrecise(exponent.max(0) as u8));
+        }
+        let digits = format!(
+            "{}{}{}",
+            integer,
+            fraction,
+            "0".repeat(exponent as usize - fraction.len())
+        );
+        digits.parse::<u128>().map_err(|_| {
+            if digits.chars().all(|c| c.is_ascii_digit()) {
+                Error::Overflow
+            } else {
+                Error::NotANumber(input.to_owned())
+            }
+        })
+    }
+}
+
+/// Check if field type name (`Balance`, `T::Balance`, `BalanceOf<T>`) indicates balance
+///
+/// Only the last path segment without generics counts, so that `BalanceStatus` or
+/// `Vec<BalanceOf<T>>` are not taken for balances.
+pub fn is_balance_type_name(type_name: &Option<String>) -> bool {
+    let name = match type_name {
+        Some(a) => a.trim(),
+        None => return false,
+    };
+    let name = name
+        .strip_prefix("Compact<")
+        .and_then(|a| a.strip_suffix('>'))
+        .unwrap_or(name);
+    // `<T as Config>::Balance` starts with generics, these are dropped with path
+    let name = match name.find('<') {
+        Some(a) if a > 0 => &name[..a],
+        _ => name,
+    };
+    let last = name.rsplit("::").next().unwrap_or(name);
+    last.ends_with("Balance") || last.ends_with("BalanceOf")
+}
+
+/// Balances and tips are marked by substrate parser through specialty
+pub fn is_balance_specialty(specialty: &SpecialtyUnsignedInteger) -> bool {
+    matches!(
+        specialty,
+        SpecialtyUnsignedInteger::Balance | SpecialtyUnsignedInteger::Tip
+    )
+}
+
+/// Unsigned value of field, if it is set
+pub fn unsigned_value(input: &UnsignedToFill) -> Option<u128> {
+    match input {
+        UnsignedToFill::U8(a) => a.map(|b| b as u128),
+        UnsignedToFill::U16(a) => a.map(|b| b as u128),
+        UnsignedToFill::U32(a) => a.map(|b| b as u128),
+        UnsignedToFill::U64(a) => a.map(|b| b as u128),
+        UnsignedToFill::U128(a) => *a,
+    }
+}
+
+/// Unsigned content of primitive field, with balance indicator from specialty
+pub fn balance_candidate(input: &TypeContentToFill) -> Option<(&UnsignedToFill, bool)> {
+    match input {
+        TypeContentToFill::Primitive(PrimitiveToFill::CompactUnsigned(a)) => {
+            Some((&a.content, is_balance_specialty(&a.specialty)))
+        }
+        TypeContentToFill::Primitive(PrimitiveToFill::Unsigned(a)) => {
+            Some((&a.content, is_balance_specialty(&a.specialty)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn westend() -> Units {
+        let specs = serde_json::json!({ "tokenDecimals": 12, "tokenSymbol": "WND" });
+        Units::from_specs(specs.as_object().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn specs_as_arrays() {
+        let specs = serde_json::json!({ "tokenDecimals": [10, 12], "tokenSymbol": ["DOT", "X"] });
+        let units = Units::from_specs(specs.as_object().unwrap()).unwrap();
+        assert_eq!(units.format(15_000_000_000), "1.5 DOT");
+    }
+
+    #[test]
+    fn too_many_decimals() {
+        let specs = serde_json::json!({ "tokenDecimals": 39, "tokenSymbol": "BIG" });
+        assert!(matches!(
+            Units::from_specs(specs.as_object().unwrap()),
+            Err(Error::TooManyDecimals(39))
+        ));
+        let specs = serde_json::json!({ "tokenDecimals": 38, "tokenSymbol": "BIG" });
+        let units = Units::from_specs(specs.as_object().unwrap()).unwrap();
+        assert_eq!(
+            units.format(u128::MAX),
+            "3.40282366920938463463374607431768211455 BIG"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let units = westend();
+        assert_eq!(units.parse("1.5").unwrap(), 1_500_000_000_000);
+        assert_eq!(units.parse("1.5 WND").unwrap(), 1_500_000_000_000);
+        assert_eq!(units.parse("1.5wnd").unwrap(), 1_500_000_000_000);
+        assert_eq!(units.parse("250m").unwrap(), 250_000_000_000);
+        assert_eq!(units.parse("250 mWND").unwrap(), 250_000_000_000);
+        assert_eq!(units.parse("3u").unwrap(), 3_000_000);
+        assert_eq!(units.parse("3 µWND").unwrap(), 3_000_000);
+        assert_eq!(units.parse("1.5n").unwrap(), 1_500);
+        assert_eq!(units.parse("2k").unwrap(), 2_000_000_000_000_000);
+        assert_eq!(units.parse("12 planck").unwrap(), 12);
+        assert_eq!(units.parse("1_000").unwrap(), 1_000_000_000_000_000);
+        assert_eq!(units.parse(".5").unwrap(), 500_000_000_000);
+        assert_eq!(units.parse("1.50000").unwrap(), 1_500_000_000_000);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let units = westend();
+        assert!(matches!(units.parse(""), Err(Error::NotANumber(_))));
+        assert!(matches!(units.parse("WND"), Err(Error::NotANumber(_))));
+        assert!(matches!(units.parse("."), Err(Error::NotANumber(_))));
+        assert!(matches!(units.parse("1.2.3"), Err(Error::NotANumber(_))));
+        assert!(matches!(units.parse("1 DOT"), Err(Error::UnknownUnit(_))));
+        assert!(matches!(units.parse("1 mDOT"), Err(Error::UnknownUnit(_))));
+        assert!(matches!(
+            units.parse("1.5 planck"),
+            Err(Error::TooPrecise(0))
+        ));
+        assert!(matches!(
+            units.parse("0.0000000000001"),
+            Err(Error::TooPrecise(12))
+        ));
+        assert!(matches!(units.parse("0.0001n"), Err(Error::TooPrecise(3))));
+        assert!(matches!(
+            units.parse("340282366920938463463374607431768211456 planck"),
+            Err(Error::Overflow)
+        ));
+        assert!(matches!(
+            units.parse("1000000000000000000000000000"),
+            Err(Error::Overflow)
+        ));
+    }
+
+    #[test]
+    fn format() {
+        let units = westend();
+        assert_eq!(units.format(0), "0 WND");
+        assert_eq!(units.format(1), "0.000000000001 WND");
+        assert_eq!(units.format(1_000_000_000_000), "1 WND");
+        assert_eq!(units.format(1_234_500_000_000), "1.2345 WND");
+        assert_eq!(Units::planck().format(12), "12 planck");
+    }
+
+    #[test]
+    fn round_trip() {
+        let units = westend();
+        for planck in [1, 999, 1_000_000_000_000, 1_234_567_890_123_456, u128::MAX] {
+            assert_eq!(units.parse(&units.format(planck)).unwrap(), planck);
+        }
+    }
+
+    #[test]
+    fn balance_type_names() {
+        for name in [
+            "Balance",
+            "T::Balance",
+            "BalanceOf<T>",
+            "BalanceOf<T, I>",
+            "<T as Config>::Balance",
+            "<T as pallet_balances::Config<I>>::Balance",
+            "Compact<T::Balance>",
+            "DepositBalanceOf<T, I>",
+        ] {
+            assert!(is_balance_type_name(&Some(name.to_string())), "{}", name);
+        }
+        for name in [
+            "BalanceStatus",
+            "Vec<BalanceOf<T>>",
+            "Option<T::Balance>",
+            "u128",
+        ] {
+            assert!(!is_balance_type_name(&Some(name.to_string())), "{}", name);
+        }
+        assert!(!is_balance_type_name(&None));
+    }
+}
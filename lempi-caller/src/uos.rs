@@ -0,0 +1,176 @@
+use parity_scale_codec::{Compact, Encode};
+
+use primitive_types::H256;
+
+use qrcode::{render::unicode::Dense1x2, QrCode};
+
+use crate::author::{Crypto, Signature};
+
+/// `S`, substrate payload in Universal Offline Signatures, as read by Kampela and Polkadot Vault
+const SUBSTRATE_ID: u8 = 0x53;
+/// Transaction, with genesis hash appended for network identification
+const SIGN_TRANSACTION: u8 = 0x02;
+
+const ED25519: u8 = 0x00;
+const SR25519: u8 = 0x01;
+const ECDSA: u8 = 0x02;
+
+#[derive(Debug)]
+pub enum Error {
+    Crypto(Crypto),
+    Qr(String),
+    Signature(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Crypto(a) => write!(f, "no UOS signing for {:?} keys", a),
+            Error::Qr(a) => write!(f, "could not make QR code: {}", a),
+            Error::Signature(a) => write!(f, "invalid signature: {}", a),
+        }
+    }
+}
+
+fn crypto_id(crypto: Crypto) -> Result<u8, Error> {
+    match crypto {
+        Crypto::Ed25519 => Ok(ED25519),
+        Crypto::Sr25519 => Ok(SR25519),
+        Crypto::Ecdsa => Ok(ECDSA),
+        Crypto::Ethereum => Err(Error::Crypto(crypto)),
+    }
+}
+
+/// Payload to sign transaction, as polkadot-js makes it for external signers
+///
+/// Public key, call with length prefix, extensions, then genesis hash.
+pub fn transaction(
+    crypto: Crypto,
+    public: &[u8],
+    call: &[u8],
+    extensions: &[u8],
+    genesis_hash: H256,
+) -> Result<Vec<u8>, Error> {
+    Ok([
+        &[SUBSTRATE_ID, crypto_id(crypto)?, SIGN_TRANSACTION][..],
+        public,
+        &Compact(call.len() as u32).encode(),
+        call,
+        extensions,
+        genesis_hash.as_bytes(),
+    ]
+    .concat())
+}
+
+/// Payload in single legacy multipart frame: not-raptorq flag, frame count, frame index
+pub fn frame(payload: &[u8]) -> Vec<u8> {
+    [&[0x00, 0x00, 0x01, 0x00, 0x00][..], payload].concat()
+}
+
+/// QR code drawn with half-block characters, light on dark terminal
+pub fn qr_text(data: &[u8]) -> Result<String, Error> {
+    let code = QrCode::new(data).map_err(|e| Error::Qr(e.to_string()))?;
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build()
+        .replace('\n', "\r\n"))
+}
+
+/// Read signature returned by external signer for key of given crypto
+///
+/// Signature is bare, or prefixed with crypto id as in `MultiSignature`; prefix must match crypto.
+pub fn parse_signature(input: &str, crypto: Crypto) -> Result<Signature, Error> {
+    let bytes = hex::decode(input.trim().trim_start_matches("0x"))
+        .map_err(|e| Error::Signature(e.to_string()))?;
+    let id = crypto_id(crypto)?;
+    let len = match crypto {
+        Crypto::Ecdsa => 65,
+        _ => 64,
+    };
+    let bare = match bytes.len() {
+        a if a == len => &bytes[..],
+        a if a == len + 1 && bytes[0] == id => &bytes[1..],
+        a if a == len + 1 => {
+            return Err(Error::Signature(format!(
+                "prefix {} is not of {:?} signature",
+                bytes[0], crypto
+            )))
+        }
+        a => return Err(Error::Signature(format!("unexpected length {}", a))),
+    };
+    match crypto {
+        Crypto::Sr25519 => Ok(Signature::Sr25519(bare.try_into().unwrap())),
+        Crypto::Ed25519 => Ok(Signature::Ed25519(bare.try_into().unwrap())),
+        Crypto::Ecdsa => Ok(Signature::Ecdsa(bare.try_into().unwrap())),
+        Crypto::Ethereum => Err(Error::Crypto(crypto)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transaction_layout() {
+        let genesis_hash = H256::repeat_byte(0xaa);
+        let payload =
+            transaction(Crypto::Sr25519, &[1; 32], &[5, 0, 7], &[9, 9], genesis_hash).unwrap();
+        let expected = [
+            &[0x53, 0x01, 0x02][..],
+            &[1; 32],
+            // compact length of call
+            &[12],
+            &[5, 0, 7],
+            &[9, 9],
+            &[0xaa; 32],
+        ]
+        .concat();
+        assert_eq!(payload, expected);
+
+        let payload = transaction(Crypto::Ed25519, &[1; 32], &[], &[], genesis_hash).unwrap();
+        assert_eq!(payload[..4], [0x53, 0x00, 0x02, 1]);
+        assert!(transaction(Crypto::Ethereum, &[1; 20], &[], &[], genesis_hash).is_err());
+    }
+
+    #[test]
+    fn single_frame() {
+        assert_eq!(
+            frame(&[0xde, 0xad]),
+            [0x00, 0x00, 0x01, 0x00, 0x00, 0xde, 0xad]
+        );
+    }
+
+    #[test]
+    fn signature_of_expected_crypto() {
+        let bare = "ab".repeat(64);
+        assert!(matches!(
+            parse_signature(&bare, Crypto::Sr25519),
+            Ok(Signature::Sr25519(a)) if a == [0xab; 64]
+        ));
+        assert!(matches!(
+            parse_signature(&bare, Crypto::Ed25519),
+            Ok(Signature::Ed25519(a)) if a == [0xab; 64]
+        ));
+        assert!(matches!(
+            parse_signature(&format!(" 0x01{}\n", bare), Crypto::Sr25519),
+            Ok(Signature::Sr25519(_))
+        ));
+        assert!(matches!(
+            parse_signature(&format!("00{}", bare), Crypto::Ed25519),
+            Ok(Signature::Ed25519(_))
+        ));
+        assert!(matches!(
+            parse_signature(&format!("02{}ab", bare), Crypto::Ecdsa),
+            Ok(Signature::Ecdsa(a)) if a == [0xab; 65]
+        ));
+
+        // ed25519 prefix on signature for sr25519 key
+        assert!(parse_signature(&format!("00{}", bare), Crypto::Sr25519).is_err());
+        assert!(parse_signature(&bare[2..], Crypto::Sr25519).is_err());
+        assert!(parse_signature("0xzz", Crypto::Sr25519).is_err());
+        assert!(parse_signature(&bare, Crypto::Ethereum).is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use primitive_types::H256;
+
+use std::num::{IntErrorKind, ParseIntError};
+use std::str::FromStr;
+
+use substrate_constructor::fill_prepare::{
+    PrimitiveToFill, RegularPrimitiveToFill, SpecialTypeToFill, TypeContentToFill, UnsignedToFill,
+};
+
+use crate::author::{self, parse_ss58};
+
+/// Why typed value does not fit the field
+#[derive(Debug)]
+pub enum Error {
+    InvalidAddress(String),
+    InvalidHex(String),
+    NotABool(String),
+    NotAChar(String),
+    NotANumber(String),
+    Overflow(&'static str),
+    WrongLength { expected: usize, found: usize },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::InvalidAddress(a) => write!(f, "invalid address: {}", a),
+            Error::InvalidHex(a) => write!(f, "invalid hex: {}", a),
+            Error::NotABool(a) => write!(f, "{} is not true or false", a),
+            Error::NotAChar(a) => write!(f, "{} is not a single character", a),
+            Error::NotANumber(a) => write!(f, "{} is not a number", a),
+            Error::Overflow(a) => write!(f, "value does not fit into {}", a),
+            Error::WrongLength { expected, found } => {
+                write!(f, "wrong length: {} bytes, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+/// Check typed text against field it goes to; empty text leaves field as it is
+///
+/// Balances are checked when parsed in units, accounts picked from address book are always
+/// valid.
+pub fn check(content: &TypeContentToFill, input: &str) -> Result<(), Error> {
+    // bytes take text as is, with spaces, or `0x`-prefixed hex
+    match content {
+        TypeContentToFill::ArrayU8(a) => {
+            return match bytes(input)?.len() {
+                0 => Ok(()),
+                found if found != a.len as usize => Err(Error::WrongLength {
+                    expected: a.len as usize,
+                    found,
+                }),
+                _ => Ok(()),
+            }
+        }
+        TypeContentToFill::SequenceU8(_) => return bytes(input).map(|_| ()),
+        _ => {}
+    }
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(());
+    }
+    match content {
+        TypeContentToFill::Primitive(
+            PrimitiveToFill::CompactUnsigned(a) | PrimitiveToFill::Unsigned(a),
+        ) => unsigned(&a.content, input),
+        TypeContentToFill::Primitive(PrimitiveToFill::Regular(a)) => regular(a, input),
+        TypeContentToFill::SequenceRegular(_) => usize::from_str(input)
+            .map(|_| ())
+            .map_err(|e| number_error(e, input, "usize")),
+        TypeContentToFill::SpecialType(SpecialTypeToFill::AccountId32(_)) => {
+            account(input).map(|_| ())
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::H256 { .. }) => {
+            let bytes = hex::decode(input.trim_start_matches("0x"))
+                .map_err(|e| Error::InvalidHex(e.to_string()))?;
+            match bytes.len() {
+                32 => Ok(()),
+                found => Err(Error::WrongLength {
+                    expected: 32,
+                    found,
+                }),
+            }
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::PerU16 { .. }) => {
+            parts(input, u16::MAX as u64, "PerU16")
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::Perbill { .. }) => {
+            parts(input, 1_000_000_000, "Perbill")
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::Percent { .. }) => {
+            parts(input, 100, "Percent")
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::Permill { .. }) => {
+            parts(input, 1_000_000, "Permill")
+        }
+        TypeContentToFill::SpecialType(SpecialTypeToFill::Perquintill { .. }) => {
+            parts(input, 1_000_000_000_000_000_000, "Perquintill")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Bytes typed as `0x`-prefixed hex, or as text
+pub fn bytes(input: &str) -> Result<Vec<u8>, Error> {
+    match input.trim().strip_prefix("0x") {
+        Some(a) => hex::decode(a).map_err(|e| Error::InvalidHex(e.to_string())),
+        None => Ok(input.as_bytes().to_vec()),
+    }
+}
+
+/// Account typed as SS58 address
+pub fn account(input: &str) -> Result<H256, Error> {
+    match parse_ss58(input) {
+        Ok((a, _)) => Ok(a),
+        Err(author::Error::InvalidAddress(e)) => Err(Error::InvalidAddress(e)),
+        Err(e) => Err(Error::InvalidAddress(format!("{:?}", e))),
+    }
+}
+
+fn unsigned(content: &UnsignedToFill, input: &str) -> Result<(), Error> {
+    let value = u128::from_str(input).map_err(|e| number_error(e, input, "u128"))?;
+    let (max, name) = match content {
+        UnsignedToFill::U8(_) => (u8::MAX as u128, "u8"),
+        UnsignedToFill::U16(_) => (u16::MAX as u128, "u16"),
+        UnsignedToFill::U32(_) => (u32::MAX as u128, "u32"),
+        UnsignedToFill::U64(_) => (u64::MAX as u128, "u64"),
+        UnsignedToFill::U128(_) => (u128::MAX, "u128"),
+    };
+    if value > max {
+        Err(Error::Overflow(name))
+    } else {
+        Ok(())
+    }
+}
+
+fn regular(content: &RegularPrimitiveToFill, input: &str) -> Result<(), Error> {
+    match content {
+        RegularPrimitiveToFill::Bool(_) => bool::from_str(input)
+            .map(|_| ())
+            .map_err(|_| Error::NotABool(input.to_string())),
+        RegularPrimitiveToFill::Char(_) => char::from_str(input)
+            .map(|_| ())
+            .map_err(|_| Error::NotAChar(input.to_string())),
+        RegularPrimitiveToFill::I8(_) => signed::<i8>(input, "i8"),
+        RegularPrimitiveToFill::I16(_) => signed::<i16>(input, "i16"),
+        RegularPrimitiveToFill::I32(_) => signed::<i32>(input, "i32"),
+        RegularPrimitiveToFill::I64(_) => signed::<i64>(input, "i64"),
+        RegularPrimitiveToFill::I128(_) => signed::<i128>(input, "i128"),
+        _ => Ok(()),
+    }
+}
+
+fn signed<T: FromStr<Err = ParseIntError>>(input: &str, name: &'static str) -> Result<(), Error> {
+    T::from_str(input)
+        .map(|_| ())
+        .map_err(|e| number_error(e, input, name))
+}
+
+/// Parts of per-thing, e.g. `Perbill` takes up to a billion parts
+fn parts(input: &str, max: u64, name: &'static str) -> Result<(), Error> {
+    let value = u64::from_str(input).map_err(|e| number_error(e, input, name))?;
+    if value > max {
+        Err(Error::Overflow(name))
+    } else {
+        Ok(())
+    }
+}
+
+fn number_error(error: ParseIntError, input: &str, name: &'static str) -> Error {
+    match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => Error::Overflow(name),
+        _ => Error::NotANumber(input.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use substrate_constructor::fill_prepare::{
+        ArrayU8ToFill, SequenceU8ToFill, SpecialtyUnsignedToFill,
+    };
+    use substrate_parser::special_indicators::{SpecialtyH256, SpecialtyUnsignedInteger};
+
+    fn unsigned_field(content: UnsignedToFill) -> TypeContentToFill {
+        TypeContentToFill::Primitive(PrimitiveToFill::Unsigned(SpecialtyUnsignedToFill {
+            content,
+            specialty: SpecialtyUnsignedInteger::None,
+        }))
+    }
+
+    fn regular_field(content: RegularPrimitiveToFill) -> TypeContentToFill {
+        TypeContentToFill::Primitive(PrimitiveToFill::Regular(content))
+    }
+
+    fn array_field(len: u32) -> TypeContentToFill {
+        TypeContentToFill::ArrayU8(ArrayU8ToFill {
+            content: Vec::new(),
+            info_element: Vec::new(),
+            len,
+        })
+    }
+
+    #[test]
+    fn unsigned_overflow_per_width() {
+        for (content, max) in [
+            (UnsignedToFill::U8(None), u8::MAX as u128),
+            (UnsignedToFill::U16(None), u16::MAX as u128),
+            (UnsignedToFill::U32(None), u32::MAX as u128),
+            (UnsignedToFill::U64(None), u64::MAX as u128),
+        ] {
+            let field = unsigned_field(content);
+            assert!(check(&field, &max.to_string()).is_ok());
+            assert!(matches!(
+                check(&field, &(max + 1).to_string()),
+                Err(Error::Overflow(_))
+            ));
+        }
+        let field = unsigned_field(UnsignedToFill::U128(None));
+        assert!(check(&field, &u128::MAX.to_string()).is_ok());
+        assert!(matches!(
+            check(&field, "340282366920938463463374607431768211456"),
+            Err(Error::Overflow("u128"))
+        ));
+        assert!(matches!(check(&field, "-1"), Err(Error::NotANumber(_))));
+        assert!(matches!(check(&field, "1.5"), Err(Error::NotANumber(_))));
+        // empty text leaves field as it is
+        assert!(check(&field, "  ").is_ok());
+    }
+
+    #[test]
+    fn signed_and_other_primitives() {
+        let field = regular_field(RegularPrimitiveToFill::I8(None));
+        assert!(check(&field, "-128").is_ok());
+        assert!(check(&field, " 127 ").is_ok());
+        assert!(matches!(check(&field, "-129"), Err(Error::Overflow("i8"))));
+        assert!(matches!(check(&field, "128"), Err(Error::Overflow("i8"))));
+        let field = regular_field(RegularPrimitiveToFill::I64(None));
+        assert!(check(&field, &i64::MIN.to_string()).is_ok());
+        assert!(matches!(check(&field, "- 1"), Err(Error::NotANumber(_))));
+
+        let field = regular_field(RegularPrimitiveToFill::Bool(None));
+        assert!(check(&field, "true").is_ok());
+        assert!(check(&field, "false").is_ok());
+        assert!(matches!(check(&field, "yes"), Err(Error::NotABool(_))));
+        let field = regular_field(RegularPrimitiveToFill::Char(None));
+        assert!(check(&field, "ä").is_ok());
+        assert!(matches!(check(&field, "ab"), Err(Error::NotAChar(_))));
+    }
+
+    #[test]
+    fn per_thing_parts() {
+        let perbill = TypeContentToFill::SpecialType(SpecialTypeToFill::Perbill {
+            value: None,
+            is_compact: false,
+        });
+        assert!(check(&perbill, "1000000000").is_ok());
+        assert!(matches!(
+            check(&perbill, "1000000001"),
+            Err(Error::Overflow("Perbill"))
+        ));
+        let percent = TypeContentToFill::SpecialType(SpecialTypeToFill::Percent {
+            value: None,
+            is_compact: true,
+        });
+        assert!(check(&percent, "100").is_ok());
+        assert!(matches!(
+            check(&percent, "101"),
+            Err(Error::Overflow("Percent"))
+        ));
+        let per_u16 = TypeContentToFill::SpecialType(SpecialTypeToFill::PerU16 {
+            value: None,
+            is_compact: false,
+        });
+        assert!(check(&per_u16, "65535").is_ok());
+        assert!(check(&per_u16, "65536").is_err());
+        let permill = TypeContentToFill::SpecialType(SpecialTypeToFill::Permill {
+            value: None,
+            is_compact: false,
+        });
+        assert!(check(&permill, "1000001").is_err());
+        let perquintill = TypeContentToFill::SpecialType(SpecialTypeToFill::Perquintill {
+            value: None,
+            is_compact: false,
+        });
+        assert!(check(&perquintill, "1000000000000000000").is_ok());
+        assert!(check(&perquintill, "1000000000000000001").is_err());
+    }
+
+    #[test]
+    fn hash_and_array_length() {
+        let hash = TypeContentToFill::SpecialType(SpecialTypeToFill::H256 {
+            hash: None,
+            specialty: SpecialtyH256::None,
+        });
+        assert!(check(&hash, &format!("0x{}", "ab".repeat(32))).is_ok());
+        assert!(check(&hash, &"ab".repeat(32)).is_ok());
+        assert!(matches!(
+            check(&hash, &format!("0x{}", "ab".repeat(31))),
+            Err(Error::WrongLength {
+                expected: 32,
+                found: 31
+            })
+        ));
+        assert!(matches!(check(&hash, "0xzz"), Err(Error::InvalidHex(_))));
+
+        let array = array_field(4);
+        assert!(check(&array, "0x01020304").is_ok());
+        assert!(check(&array, "abcd").is_ok());
+        assert!(matches!(
+            check(&array, "0x010203"),
+            Err(Error::WrongLength {
+                expected: 4,
+                found: 3
+            })
+        ));
+        // spaces are part of text
+        assert!(matches!(
+            check(&array, " abcd"),
+            Err(Error::WrongLength {
+                expected: 4,
+                found: 5
+            })
+        ));
+        assert!(check(&array, "").is_ok());
+    }
+
+    #[test]
+    fn hex_or_text_bytes() {
+        assert_eq!(bytes("0x6869").unwrap(), b"hi");
+        assert_eq!(bytes(" 0x6869 ").unwrap(), b"hi");
+        assert_eq!(bytes("hi there").unwrap(), b"hi there");
+        assert_eq!(bytes("0X6869").unwrap(), b"0X6869");
+        assert!(matches!(bytes("0x686"), Err(Error::InvalidHex(_))));
+
+        let sequence = TypeContentToFill::SequenceU8(SequenceU8ToFill {
+            content: Vec::new(),
+            info_element: Vec::new(),
+        });
+        assert!(check(&sequence, "any text").is_ok());
+        assert!(check(&sequence, "0xzz").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use parity_scale_codec::{DecodeAll, Encode};
+
+use primitive_types::H256;
+
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+use crate::crypto::VrfPublic;
+use crate::message::parse_message;
+use crate::sr25519::{
+    vrf::{VrfSignData, VrfSignature, VrfTranscript},
+    Public,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidHex(String),
+    InvalidSignature(String),
+    InvalidTranscript(String),
+    NotVerified,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::InvalidHex(a) => write!(f, "invalid hex: {}", a),
+            Error::InvalidSignature(a) => write!(f, "invalid VRF signature: {}", a),
+            Error::InvalidTranscript(a) => write!(f, "invalid transcript: {}", a),
+            Error::NotVerified => write!(f, "VRF signature does not match public key and input"),
+        }
+    }
+}
+
+/// Transcript from `label; domain: data; ...`
+///
+/// Data is text or `0x`-prefixed hex, as in messages. Merlin wants static labels; these are
+/// interned, so each distinct label is leaked once.
+pub fn parse_transcript(input: &str) -> Result<VrfSignData, Error> {
+    let mut parts = input.split(';').map(str::trim);
+    let label = match parts.next() {
+        Some(a) if !a.is_empty() => a,
+        _ => return Err(Error::InvalidTranscript("label is missing".to_string())),
+    };
+    let mut data = Vec::new();
+    for part in parts.filter(|a| !a.is_empty()) {
+        let (domain, message) = part
+            .split_once(':')
+            .ok_or(Error::InvalidTranscript(format!("no domain in {}", part)))?;
+        let message =
+            parse_message(message.trim()).map_err(|e| Error::InvalidTranscript(e.to_string()))?;
+        data.push((intern(domain.trim()), message));
+    }
+    let data: Vec<(&'static [u8], &[u8])> = data.iter().map(|(a, b)| (*a, &b[..])).collect();
+    Ok(VrfTranscript::new(intern(label), &data).into_sign_data())
+}
+
+/// Labels already given out, kept for the whole run
+static LABELS: OnceLock<Mutex<HashSet<&'static [u8]>>> = OnceLock::new();
+
+fn intern(label: &str) -> &'static [u8] {
+    let mut labels = LABELS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    match labels.get(label.as_bytes()) {
+        Some(a) => a,
+        None => {
+            let a: &'static [u8] = Box::leak(label.as_bytes().to_vec().into_boxed_slice());
+            labels.insert(a);
+            a
+        }
+    }
+}
+
+/// Pre-output, proof, and whole signature as encoded on chain, in hex
+pub fn describe(signature: &VrfSignature) -> String {
+    format!(
+        "Pre-output: 0x{}\r\nProof: 0x{}\r\nSignature: 0x{}",
+        hex::encode(signature.pre_output.encode()),
+        hex::encode(signature.proof.encode()),
+        hex::encode(signature.encode())
+    )
+}
+
+/// Check VRF signature, encoded pre-output with proof, of sr25519 public key over transcript
+pub fn verify(public: &H256, data: &VrfSignData, signature: &str) -> Result<(), Error> {
+    let bytes = hex::decode(signature.trim().trim_start_matches("0x"))
+        .map_err(|e| Error::InvalidHex(e.to_string()))?;
+    let signature = VrfSignature::decode_all(&mut &bytes[..])
+        .map_err(|e| Error::InvalidSignature(e.to_string()))?;
+    if Public::from_raw(public.0).vrf_verify(data, &signature) {
+        Ok(())
+    } else {
+        Err(Error::NotVerified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::crypto::{Pair as _, VrfSecret};
+    use crate::sr25519::Pair;
+
+    const TRANSCRIPT: &str = "lempi-vrf; epoch: 0x2a; slot: twelve";
+
+    fn alice() -> Pair {
+        Pair::from_string("//Alice", None).unwrap()
+    }
+
+    /// Signature hex as shown by `describe`
+    fn described(signature: &VrfSignature) -> String {
+        describe(signature)
+            .rsplit("Signature: ")
+            .next()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn sign_describe_verify() {
+        let pair = alice();
+        let public = H256(pair.public().0);
+        let signature = pair.vrf_sign(&parse_transcript(TRANSCRIPT).unwrap());
+        let hex = described(&signature);
+        assert!(describe(&signature).starts_with(&format!(
+            "Pre-output: 0x{}",
+            hex::encode(signature.pre_output.encode())
+        )));
+
+        // transcript is parsed anew, as when verified later
+        let data = parse_transcript(TRANSCRIPT).unwrap();
+        verify(&public, &data, &hex).unwrap();
+        // spaces around parts do not matter
+        let data = parse_transcript(" lempi-vrf ;epoch:0x2a ; slot:twelve;").unwrap();
+        verify(&public, &data, &hex).unwrap();
+    }
+
+    #[test]
+    fn tampered_transcript_or_other_key() {
+        let pair = alice();
+        let public = H256(pair.public().0);
+        let hex = described(&pair.vrf_sign(&parse_transcript(TRANSCRIPT).unwrap()));
+
+        for tampered in [
+            "lempi-vrf; epoch: 0x2b; slot: twelve",
+            "lempi-vrf; epoch: 0x2a; slot: thirteen",
+            "lempi-vrf; slot: twelve; epoch: 0x2a",
+            "lempi-vrx; epoch: 0x2a; slot: twelve",
+        ] {
+            let data = parse_transcript(tampered).unwrap();
+            assert!(matches!(
+                verify(&public, &data, &hex),
+                Err(Error::NotVerified)
+            ));
+        }
+
+        let bob = H256(Pair::from_string("//Bob", None).unwrap().public().0);
+        let data = parse_transcript(TRANSCRIPT).unwrap();
+        assert!(matches!(verify(&bob, &data, &hex), Err(Error::NotVerified)));
+        assert!(matches!(
+            verify(&public, &data, &hex[..hex.len() - 2]),
+            Err(Error::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn malformed_transcripts() {
+        assert!(parse_transcript("").is_err());
+        assert!(parse_transcript("; epoch: 1").is_err());
+        assert!(parse_transcript("lempi-vrf; epoch").is_err());
+        assert!(parse_transcript("lempi-vrf; epoch: 0xzz").is_err());
+    }
+
+    #[test]
+    fn labels_are_interned() {
+        let a = intern("lempi-test");
+        let b = intern(&String::from("lempi-test"));
+        assert_eq!(a.as_ptr(), b.as_ptr());
+        assert_ne!(a.as_ptr(), intern("lempi-other").as_ptr());
+    }
+}